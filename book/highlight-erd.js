@@ -10,7 +10,7 @@ hljs.registerLanguage("erd", function (hljs) {
   };
   var special = {
     begin:
-      /entity|attribute|id|relation|one|exactly|multiple|required|optional|table|from|type/,
+      /weak|entity|attribute|identifying|id|relation|one|exactly|between|at_least|multiple|required|optional|as|unique|default|check|cascade|restrict|table|from|type/,
     className: "keyword",
   };
 
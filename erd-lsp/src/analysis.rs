@@ -0,0 +1,362 @@
+use erd_script::ast::{Expr, Ident, Span};
+use erd_script::diagnostic::{Diagnostic, ToDiagnostic};
+use erd_script::erd::ERD;
+use erd_script::physical::{PhysicalDescription, PhysicalERDError};
+use erd_script::project::Project;
+use std::convert::TryFrom;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymbolKind {
+    Entity,
+    Relation,
+}
+
+/// The name of an entity or relation in one of the files of a project, `start` and `end`
+/// are byte offsets in that file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Occurrence {
+    pub name: Ident,
+    pub kind: SymbolKind,
+    pub file: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub definition: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompletionKind {
+    Keyword,
+    DataType,
+    Entity,
+    Relation,
+}
+
+const KEYWORDS: &[&str] = &[
+    "entity",
+    "relation",
+    "weak",
+    "identifying",
+    "table",
+    "import",
+    "id",
+    "attribute",
+    "type",
+    "foreign key",
+    "from",
+    "for",
+    "one",
+    "multiple",
+    "exactly",
+    "between",
+    "at_least",
+    "optional",
+    "required",
+    "as",
+    "unique",
+    "default",
+    "check",
+    "on delete",
+    "on update",
+    "cascade",
+    "set null",
+    "restrict",
+];
+
+const DATATYPES: &[&str] = &[
+    "integer",
+    "smallinteger",
+    "autoincrement",
+    "float",
+    "boolean",
+    "date",
+    "time",
+    "datetime",
+    "uuid",
+    "text",
+    "blob",
+    "varchar",
+    "varbinary",
+    "decimal",
+    "enum",
+];
+
+/// An erd-script file that is opened in an editor, together with the files it imports
+pub struct Analysis {
+    name: String,
+    project: Project,
+    expressions: Vec<Expr>,
+    occurrences: Vec<Occurrence>,
+}
+
+impl Analysis {
+    pub fn new(name: &str, content: String) -> Self {
+        let project = Project::from_source(name, content);
+        let expressions = project.expressions();
+        let mut analysis = Self {
+            name: name.to_string(),
+            project,
+            expressions,
+            occurrences: Vec::new(),
+        };
+        analysis.occurrences = analysis.find_occurrences();
+        analysis
+    }
+
+    pub fn project(&self) -> &Project {
+        &self.project
+    }
+
+    fn find_occurrences(&self) -> Vec<Occurrence> {
+        let mut occurrences = Vec::new();
+        for expr in self.expressions.iter() {
+            match expr {
+                Expr::Entity(name, _, _, span) => {
+                    occurrences.push(occurrence(name, SymbolKind::Entity, span, true))
+                }
+                Expr::Relation(name, _, members, _, _, span) => {
+                    occurrences.push(occurrence(name, SymbolKind::Relation, span, true));
+                    occurrences.extend(members.iter().filter_map(|m| {
+                        self.reference_after(
+                            &m.entity,
+                            SymbolKind::Entity,
+                            &m.span,
+                            &["optional", "required"],
+                        )
+                    }));
+                }
+                Expr::EntityTable(_, entity, foreign_keys, span) => {
+                    occurrences.extend(self.table_source(entity, SymbolKind::Entity, span));
+                    occurrences.extend(foreign_keys.iter().filter_map(|f| {
+                        self.reference_after(&f.relation, SymbolKind::Relation, &f.span, &["for"])
+                    }));
+                }
+                Expr::RelationTable(_, relation, span) => {
+                    occurrences.extend(self.table_source(relation, SymbolKind::Relation, span))
+                }
+                Expr::Import(_, _) => (),
+            }
+        }
+        occurrences
+    }
+
+    /// The reference to `name` that follows one of `keywords` in the text of `span`, like the
+    /// entity after the optionality of a relation member
+    fn reference_after(
+        &self,
+        name: &Ident,
+        kind: SymbolKind,
+        span: &Span,
+        keywords: &[&str],
+    ) -> Option<Occurrence> {
+        let content = self.project.source(span.file.as_deref())?;
+        let text = &content[span.start..span.end];
+        let mut position = 0;
+        let mut words = text.split_whitespace().map(|word| {
+            let start = position + text[position..].find(word).unwrap();
+            position = start + word.len();
+            (start, word)
+        });
+        words.find(|(_, word)| keywords.contains(word))?;
+        let (start, word) = words.next()?;
+        (word == name.0).then(|| Occurrence {
+            start: span.start + start,
+            end: span.start + start + word.len(),
+            ..occurrence(name, kind, span, false)
+        })
+    }
+
+    /// The entity or relation of `table <name> from entity|relation <source>`, which is
+    /// the last word on the line of the table name
+    fn table_source(&self, source: &Ident, kind: SymbolKind, span: &Span) -> Option<Occurrence> {
+        let content = self.project.source(span.file.as_deref())?;
+        let rest = &content[span.end..];
+        let line = rest[..rest.find('\n').unwrap_or(rest.len())]
+            .split("//")
+            .next()
+            .unwrap_or("")
+            .trim_end();
+        let start = span.end + line.rfind(char::is_whitespace).map(|i| i + 1)?;
+        let end = span.end + line.len();
+        (content[start..end] == source.0).then(|| Occurrence {
+            name: source.clone(),
+            kind,
+            file: span.file.clone(),
+            start,
+            end,
+            definition: false,
+        })
+    }
+
+    /// The diagnostics of the opened file. The physical mapping is only checked if the
+    /// project contains tables.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.project.diagnostics();
+        let has_tables = self.expressions.iter().any(|e| {
+            matches!(
+                e,
+                Expr::EntityTable(_, _, _, _) | Expr::RelationTable(_, _, _)
+            )
+        });
+        if has_tables {
+            match PhysicalDescription::try_from(self.expressions.clone()) {
+                Ok(_) => (),
+                Err(PhysicalERDError::ERD(errors)) => {
+                    diagnostics.extend(errors.iter().map(|e| e.to_diagnostic()))
+                }
+                Err(PhysicalERDError::Physical(errors)) => {
+                    diagnostics.extend(errors.iter().map(|e| e.to_diagnostic()))
+                }
+            }
+        } else if let Err(errors) = ERD::try_from(self.expressions.clone()) {
+            diagnostics.extend(errors.iter().map(|e| e.to_diagnostic()));
+        }
+        diagnostics.retain(|d| match &d.span {
+            Some(span) => span.file.as_deref() == Some(&self.name[..]),
+            None => true,
+        });
+        diagnostics
+    }
+
+    /// The entity or relation name at `offset` in `file`
+    pub fn occurrence_at(&self, file: &str, offset: usize) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .find(|o| o.file.as_deref() == Some(file) && o.start <= offset && offset <= o.end)
+    }
+
+    pub fn definition(&self, name: &Ident) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .find(|o| o.definition && &o.name == name)
+    }
+
+    pub fn references<'a>(&'a self, name: &'a Ident) -> impl Iterator<Item = &'a Occurrence> {
+        self.occurrences.iter().filter(move |o| &o.name == name)
+    }
+
+    /// The definition of an entity or relation as erd-script, which lists its keys,
+    /// attributes and members
+    pub fn hover(&self, name: &Ident) -> Option<String> {
+        self.expressions
+            .iter()
+            .find(|e| match e {
+                Expr::Entity(n, _, _, _) | Expr::Relation(n, _, _, _, _, _) => n == name,
+                _ => false,
+            })
+            .map(|e| e.to_string())
+    }
+
+    /// The completions for the word that is typed after `line_prefix`
+    pub fn completions(&self, line_prefix: &str) -> Vec<(String, CompletionKind)> {
+        let mut words: Vec<&str> = line_prefix.split_whitespace().collect();
+        if !line_prefix.ends_with(char::is_whitespace) {
+            // The word that is being typed
+            words.pop();
+        }
+        let is_cardinality = |w: &str| {
+            ["one", "multiple"].contains(&w)
+                || ["exactly(", "between(", "at_least("]
+                    .iter()
+                    .any(|c| w.starts_with(c))
+        };
+        let entities = || self.names(SymbolKind::Entity, CompletionKind::Entity);
+        let relations = || self.names(SymbolKind::Relation, CompletionKind::Relation);
+        match words[..] {
+            [.., "type"] => DATATYPES
+                .iter()
+                .map(|d| (d.to_string(), CompletionKind::DataType))
+                .collect(),
+            [.., "from", "entity"] => entities(),
+            [.., "from", "relation"] => relations(),
+            [.., "for"] => relations(),
+            ["table", _] => vec![
+                ("from entity".to_string(), CompletionKind::Keyword),
+                ("from relation".to_string(), CompletionKind::Keyword),
+            ],
+            [cardinality, "optional" | "required"] if is_cardinality(cardinality) => entities(),
+            [cardinality] if is_cardinality(cardinality) => ["optional", "required"]
+                .iter()
+                .map(|k| (k.to_string(), CompletionKind::Keyword))
+                .collect(),
+            _ => KEYWORDS
+                .iter()
+                .map(|k| (k.to_string(), CompletionKind::Keyword))
+                .chain(entities())
+                .collect(),
+        }
+    }
+
+    fn names(&self, kind: SymbolKind, completion: CompletionKind) -> Vec<(String, CompletionKind)> {
+        let mut names: Vec<_> = self
+            .occurrences
+            .iter()
+            .filter(|o| o.definition && o.kind == kind)
+            .map(|o| (o.name.to_string(), completion))
+            .collect();
+        names.dedup();
+        names
+    }
+}
+
+fn occurrence(name: &Ident, kind: SymbolKind, span: &Span, definition: bool) -> Occurrence {
+    Occurrence {
+        name: name.clone(),
+        kind,
+        file: span.file.clone(),
+        start: span.start,
+        end: span.end,
+        definition,
+    }
+}
+
+/// Checks whether `name` can be used as the name of an entity or relation
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCRIPT: &str = "entity Person // a human\n  id id type integer\n\nentity Car\n\nrelation Owns\n  one required Person as owner\n  multiple optional Car\n\ntable person from entity Person // persons\n\ntable car from entity Car\nforeign key owner for Owns on delete cascade\n";
+
+    #[test]
+    fn find_references() {
+        let analysis = Analysis::new("model.erd", SCRIPT.to_string());
+        let person: Vec<_> = analysis
+            .references(&"Person".to_string().into())
+            .map(|o| (&SCRIPT[o.start..o.end], o.start, o.definition))
+            .collect();
+        assert_eq!(person.len(), 3);
+        assert!(person.iter().all(|(text, _, _)| *text == "Person"));
+        assert_eq!(person.iter().filter(|(_, _, d)| *d).count(), 1);
+
+        let owns = SCRIPT.rfind("Owns").unwrap();
+        let occurrence = analysis.occurrence_at("model.erd", owns + 2).unwrap();
+        assert_eq!(occurrence.kind, SymbolKind::Relation);
+        assert_eq!(
+            analysis.definition(&occurrence.name).unwrap().start,
+            SCRIPT.find("Owns").unwrap()
+        );
+        assert!(analysis.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn complete_by_context() {
+        let analysis = Analysis::new("model.erd", SCRIPT.to_string());
+        let labels = |prefix: &str| -> Vec<String> {
+            analysis
+                .completions(prefix)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect()
+        };
+        assert_eq!(labels("  one required P"), vec!["Person", "Car"]);
+        assert_eq!(labels("foreign key a for "), vec!["Owns"]);
+        assert!(labels("  id id type ").contains(&"varchar".to_string()));
+        assert_eq!(labels("  exactly(2) "), vec!["optional", "required"]);
+        assert!(labels("ent").contains(&"entity".to_string()));
+    }
+}
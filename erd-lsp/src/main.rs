@@ -0,0 +1,319 @@
+mod analysis;
+mod position;
+
+use analysis::{Analysis, CompletionKind, Occurrence};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, GotoDefinition, HoverRequest, References, Rename, Request as LspRequest,
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionResponse, DiagnosticSeverity,
+    GotoDefinitionResponse, Hover, HoverContents, HoverProviderCapability, Location, MarkupContent,
+    MarkupKind, NumberOrString, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+    WorkspaceEdit,
+};
+use std::collections::HashMap;
+use std::error::Error;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// The open documents, they are sent in full on every change
+struct Server {
+    connection: Connection,
+    documents: HashMap<Url, String>,
+}
+
+/// The name of the document in the project, its path if it is a file
+fn document_name(uri: &Url) -> String {
+    match uri.to_file_path() {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(_) => uri.to_string(),
+    }
+}
+
+impl Server {
+    fn run(mut self) -> Result<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.respond(request);
+                    self.connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => self.notify(notification)?,
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn notify(&mut self, notification: Notification) -> Result<()> {
+        match &notification.method[..] {
+            DidOpenTextDocument::METHOD => {
+                let params = notification
+                    .extract::<lsp_types::DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)?;
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                self.publish_diagnostics(&document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification.extract::<lsp_types::DidChangeTextDocumentParams>(
+                    DidChangeTextDocument::METHOD,
+                )?;
+                let uri = params.text_document.uri;
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(uri.clone(), change.text);
+                }
+                self.publish_diagnostics(&uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification.extract::<lsp_types::DidCloseTextDocumentParams>(
+                    DidCloseTextDocument::METHOD,
+                )?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.send_diagnostics(uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &Url) -> Result<()> {
+        let analysis = match self.analyze(uri) {
+            Some(analysis) => analysis,
+            None => return Ok(()),
+        };
+        let content = &self.documents[uri];
+        let diagnostics = analysis
+            .diagnostics()
+            .into_iter()
+            .map(|d| {
+                let (start, end) = d.span.as_ref().map(|s| (s.start, s.end)).unwrap_or((0, 0));
+                let message = match d.help {
+                    Some(help) => format!("{}\nhelp: {}", d.message, help),
+                    None => d.message,
+                };
+                lsp_types::Diagnostic {
+                    range: position::to_range(content, start, end),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(d.code)),
+                    source: Some("erd-script".to_string()),
+                    message,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        self.send_diagnostics(uri.clone(), diagnostics)
+    }
+
+    fn send_diagnostics(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))?;
+        Ok(())
+    }
+
+    fn analyze(&self, uri: &Url) -> Option<Analysis> {
+        let content = self.documents.get(uri)?;
+        Some(Analysis::new(&document_name(uri), content.clone()))
+    }
+
+    fn respond(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match &request.method[..] {
+            GotoDefinition::METHOD => self.handle::<GotoDefinition>(request, Self::definition),
+            References::METHOD => self.handle::<References>(request, Self::references),
+            Rename::METHOD => self.handle::<Rename>(request, Self::rename),
+            HoverRequest::METHOD => self.handle::<HoverRequest>(request, Self::hover),
+            Completion::METHOD => self.handle::<Completion>(request, Self::completion),
+            method => Err((
+                ErrorCode::MethodNotFound,
+                format!("Unsupported request {}", method),
+            )),
+        };
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err((code, message)) => Response::new_err(id, code as i32, message),
+        }
+    }
+
+    fn handle<R: LspRequest>(
+        &self,
+        request: Request,
+        f: impl FnOnce(&Self, R::Params) -> std::result::Result<R::Result, String>,
+    ) -> std::result::Result<serde_json::Value, (ErrorCode, String)> {
+        let (_, params) = request
+            .extract::<R::Params>(R::METHOD)
+            .map_err(|e| (ErrorCode::InvalidParams, format!("{:?}", e)))?;
+        let result = f(self, params).map_err(|e| (ErrorCode::InvalidParams, e))?;
+        serde_json::to_value(result).map_err(|e| (ErrorCode::InternalError, e.to_string()))
+    }
+
+    /// The analysis of the document together with the entity or relation name at the position
+    fn occurrence_at(&self, params: &TextDocumentPositionParams) -> Option<(Analysis, Occurrence)> {
+        let uri = &params.text_document.uri;
+        let analysis = self.analyze(uri)?;
+        let offset = position::to_offset(&self.documents[uri], params.position);
+        let occurrence = analysis.occurrence_at(&document_name(uri), offset)?.clone();
+        Some((analysis, occurrence))
+    }
+
+    /// The location of an occurrence, which can be in an imported file
+    fn location(&self, analysis: &Analysis, occurrence: &Occurrence) -> Option<Location> {
+        let file = occurrence.file.as_deref()?;
+        let content = analysis.project().source(Some(file))?;
+        let uri = match self.documents.keys().find(|uri| document_name(uri) == file) {
+            Some(uri) => uri.clone(),
+            None => {
+                let path = std::path::Path::new(file);
+                Url::from_file_path(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+                    .ok()?
+            }
+        };
+        Some(Location {
+            uri,
+            range: position::to_range(content, occurrence.start, occurrence.end),
+        })
+    }
+
+    fn definition(
+        &self,
+        params: lsp_types::GotoDefinitionParams,
+    ) -> std::result::Result<Option<GotoDefinitionResponse>, String> {
+        Ok(self
+            .occurrence_at(&params.text_document_position_params)
+            .and_then(|(analysis, occurrence)| {
+                let definition = analysis.definition(&occurrence.name)?;
+                self.location(&analysis, definition)
+            })
+            .map(GotoDefinitionResponse::Scalar))
+    }
+
+    fn references(
+        &self,
+        params: lsp_types::ReferenceParams,
+    ) -> std::result::Result<Option<Vec<Location>>, String> {
+        let include_declaration = params.context.include_declaration;
+        Ok(self
+            .occurrence_at(&params.text_document_position)
+            .map(|(analysis, occurrence)| {
+                analysis
+                    .references(&occurrence.name)
+                    .filter(|o| include_declaration || !o.definition)
+                    .filter_map(|o| self.location(&analysis, o))
+                    .collect()
+            }))
+    }
+
+    fn rename(
+        &self,
+        params: lsp_types::RenameParams,
+    ) -> std::result::Result<Option<WorkspaceEdit>, String> {
+        if !analysis::is_valid_name(&params.new_name) {
+            return Err(format!("{} is not a valid name.", params.new_name));
+        }
+        let (analysis, occurrence) = match self.occurrence_at(&params.text_document_position) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for location in analysis
+            .references(&occurrence.name)
+            .filter_map(|o| self.location(&analysis, o))
+        {
+            changes.entry(location.uri).or_default().push(TextEdit {
+                range: location.range,
+                new_text: params.new_name.clone(),
+            });
+        }
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+
+    fn hover(&self, params: lsp_types::HoverParams) -> std::result::Result<Option<Hover>, String> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        Ok(self
+            .occurrence_at(&params.text_document_position_params)
+            .and_then(|(analysis, occurrence)| {
+                let definition = analysis.hover(&occurrence.name)?;
+                let range =
+                    (occurrence.file.as_deref() == Some(&document_name(uri)[..])).then(|| {
+                        position::to_range(&self.documents[uri], occurrence.start, occurrence.end)
+                    });
+                Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: format!("```erd-script\n{}\n```", definition),
+                    }),
+                    range,
+                })
+            }))
+    }
+
+    fn completion(
+        &self,
+        params: lsp_types::CompletionParams,
+    ) -> std::result::Result<Option<CompletionResponse>, String> {
+        let uri = &params.text_document_position.text_document.uri;
+        let analysis = match self.analyze(uri) {
+            Some(analysis) => analysis,
+            None => return Ok(None),
+        };
+        let content = &self.documents[uri];
+        let offset = position::to_offset(content, params.text_document_position.position);
+        let line_start = content[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let items = analysis
+            .completions(&content[line_start..offset])
+            .into_iter()
+            .map(|(label, kind)| CompletionItem {
+                label,
+                kind: Some(match kind {
+                    CompletionKind::Keyword => CompletionItemKind::KEYWORD,
+                    CompletionKind::DataType => CompletionItemKind::TYPE_PARAMETER,
+                    CompletionKind::Entity => CompletionItemKind::CLASS,
+                    CompletionKind::Relation => CompletionItemKind::INTERFACE,
+                }),
+                ..Default::default()
+            })
+            .collect();
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+}
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+    Server {
+        connection,
+        documents: HashMap::new(),
+    }
+    .run()?;
+    io_threads.join()?;
+    Ok(())
+}
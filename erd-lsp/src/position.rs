@@ -0,0 +1,73 @@
+use lsp_types::{Position, Range};
+
+/// Converts a byte offset in `text` to a position, the character is counted in UTF-16 code units
+pub fn to_position(text: &str, offset: usize) -> Position {
+    let offset = floor_char_boundary(text, offset);
+    let line_start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: text[..line_start].matches('\n').count() as u32,
+        character: text[line_start..offset].encode_utf16().count() as u32,
+    }
+}
+
+/// Converts a position to a byte offset in `text`, positions past the end of a line or of
+/// the text are clamped
+pub fn to_offset(text: &str, position: Position) -> usize {
+    let line_start = text
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(|l| l.len())
+        .sum::<usize>();
+    let line = text[line_start..].split('\n').next().unwrap_or("");
+    let mut units = 0;
+    for (idx, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + idx;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+pub fn to_range(text: &str, start: usize, end: usize) -> Range {
+    Range {
+        start: to_position(text, start),
+        end: to_position(text, end),
+    }
+}
+
+fn floor_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn count_utf16_units() {
+        let text = "entity Café\n  id 𝔵\n";
+        let offset = text.find('𝔵').unwrap();
+        let position = Position {
+            line: 1,
+            character: 5,
+        };
+        assert_eq!(to_position(text, offset), position);
+        assert_eq!(to_offset(text, position), offset);
+        assert_eq!(to_position(text, offset + '𝔵'.len_utf8()).character, 7);
+        assert_eq!(
+            to_offset(
+                text,
+                Position {
+                    line: 0,
+                    character: 100
+                }
+            ),
+            text.find('\n').unwrap()
+        );
+    }
+}
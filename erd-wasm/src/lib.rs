@@ -10,17 +10,23 @@ extern "C" {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 enum ParsingError {
     Known(Vec<String>),
-    Unknown,
 }
 
 impl ParsingError {
-    fn create(item: erd_script::parser::ConsumeError) -> Self {
-        match item {
-            erd_script::parser::ConsumeError::UnknownParseError => Self::Unknown,
-            erd_script::parser::ConsumeError::ERDParseError(v) => {
-                Self::Known(v.into_iter().map(|e| format!("{:?}", e)).collect())
-            }
-        }
+    fn create(items: Vec<erd_script::parser::ConsumeError>) -> Self {
+        Self::Known(
+            items
+                .into_iter()
+                .flat_map(|item| match item {
+                    erd_script::parser::ConsumeError::ERDParseError(v) => {
+                        v.into_iter().map(|e| format!("{}", e)).collect::<Vec<_>>()
+                    }
+                    erd_script::parser::ConsumeError::ConversionError(v) => {
+                        v.into_iter().map(|e| format!("{}", e)).collect()
+                    }
+                })
+                .collect(),
+        )
     }
 }
 
@@ -53,6 +59,17 @@ pub fn compile_erd(erd_script: &str) -> JsValue {
     .unwrap_or(false.into())
 }
 
+/// Draws the ERD with the built-in svg renderer, so Graphviz isn't needed
+#[wasm_bindgen]
+pub fn compile_erd_svg(erd_script: &str) -> JsValue {
+    serde_wasm_bindgen::to_value(
+        &erd_script::erd::ERD::from_script(erd_script)
+            .map(|erd| erd_script::svg::render(&erd.to_dot()))
+            .map_err(ERDCompileError::create),
+    )
+    .unwrap_or(false.into())
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 enum PhysicalCompileError {
     ERDErrors(Vec<String>),
@@ -79,12 +96,14 @@ impl PhysicalCompileError {
 
 #[wasm_bindgen]
 pub fn compile_physical(erd_script: &str, sql_dbms: &str) -> JsValue {
-    if let Some(dbms) = SQL::from_str(sql_dbms) {
+    if let Ok(dbms) = sql_dbms.parse::<SQL>() {
         serde_wasm_bindgen::to_value(
             &erd_script::physical::PhysicalDescription::from_script(erd_script)
                 .map(|physical| {
                     let mut s = String::new();
-                    physical.to_physical().write_sql_create(&mut s, dbms);
+                    physical
+                        .to_physical()
+                        .write_sql_create(&mut s, dbms.dialect());
                     s
                 })
                 .map_err(PhysicalCompileError::create),
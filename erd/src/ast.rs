@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
 use std::hash::{Hash, Hasher};
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -22,17 +23,94 @@ impl std::convert::From<Ident> for String {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// The location of a node in an erd-script source.
+/// `start` and `end` are byte offsets, `line` and `column` are 1-based.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub file: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Whether the span points nowhere, like the default span of generated nodes
+    pub fn is_empty(&self) -> bool {
+        self.line == 0
+    }
+
+    pub fn from_pest(span: pest::Span<'_>, file: Option<&str>) -> Self {
+        let (line, column) = span.start_pos().line_col();
+        Self {
+            file: file.map(|f| f.to_string()),
+            start: span.start(),
+            end: span.end(),
+            line,
+            column,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum AttributeType {
     Normal,
     Key,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// A constraint on the values of an attribute
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AttributeModifier {
+    Required,
+    Unique,
+    /// A literal as it is written, like `'unknown'`, `0` or `true`
+    Default(String),
+    /// A sql expression
+    Check(String),
+}
+
+impl std::fmt::Display for AttributeModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Required => write!(f, "required"),
+            Self::Unique => write!(f, "unique"),
+            Self::Default(value) => write!(f, "default {}", value),
+            Self::Check(expression) => write!(f, "check({})", expression),
+        }
+    }
+}
+
+impl std::convert::TryFrom<(String, Option<String>, Span)> for AttributeModifier {
+    type Error = ConversionError;
+    fn try_from(
+        (modifier, argument, span): (String, Option<String>, Span),
+    ) -> Result<Self, Self::Error> {
+        match (&modifier[..], argument) {
+            ("required", None) => Ok(Self::Required),
+            ("unique", None) => Ok(Self::Unique),
+            ("default", Some(value)) => Ok(Self::Default(value)),
+            ("check", Some(expression)) => Ok(Self::Check(expression)),
+            _ => Err(ConversionError::UnknownAttributeModifier(modifier, span)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
     pub ident: Ident,
     pub r#type: AttributeType,
     pub datatype: Option<DataType>,
+    pub modifiers: Vec<AttributeModifier>,
+    pub span: Span,
 }
 
 impl Attribute {
@@ -50,8 +128,13 @@ impl Attribute {
             ident: new_name,
             r#type: self.r#type.clone(),
             datatype: self.datatype.clone(),
+            modifiers: self.modifiers.clone(),
+            span: self.span.clone(),
         }
     }
+    pub fn is_required(&self) -> bool {
+        self.modifiers.contains(&AttributeModifier::Required)
+    }
 }
 
 impl Hash for Attribute {
@@ -60,26 +143,62 @@ impl Hash for Attribute {
     }
 }
 
-impl std::convert::From<(String, String, Option<String>)> for Attribute {
-    fn from((r#type, name, datatype): (String, String, Option<String>)) -> Self {
+impl std::fmt::Display for Attribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.r#type {
+            AttributeType::Normal => write!(f, "attribute {}", self.ident)?,
+            AttributeType::Key => write!(f, "id {}", self.ident)?,
+        }
+        if let Some(datatype) = &self.datatype {
+            write!(f, " type {}", datatype)?;
+        }
+        for modifier in self.modifiers.iter() {
+            write!(f, " {}", modifier)?;
+        }
+        Ok(())
+    }
+}
+
+/// (type, name, datatype, Vec<(modifier, argument)>, span)
+type ParsedAttribute = (
+    String,
+    String,
+    Option<String>,
+    Vec<(String, Option<String>)>,
+    Span,
+);
+
+impl std::convert::TryFrom<ParsedAttribute> for Attribute {
+    type Error = ConversionError;
+    fn try_from(
+        (r#type, name, datatype, modifiers, span): ParsedAttribute,
+    ) -> Result<Self, Self::Error> {
         let ident = name.into();
-        Self {
+        Ok(Self {
             ident,
             r#type: match &r#type[..] {
                 "id" => AttributeType::Key,
                 "attribute" => AttributeType::Normal,
-                _ => unreachable!(),
+                _ => return Err(ConversionError::UnknownAttributeType(r#type, span)),
             },
-            datatype: datatype.map(|d| d.into()),
-        }
+            datatype: datatype.map(|d| (d, span.clone()).try_into()).transpose()?,
+            modifiers: modifiers
+                .into_iter()
+                .map(|(m, a)| (m, a, span.clone()).try_into())
+                .collect::<Result<_, _>>()?,
+            span,
+        })
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum RelationCardinality {
     One,
     Multiple,
     Exact(usize),
+    /// Between a minimum and a maximum, both included
+    Between(usize, usize),
+    AtLeast(usize),
 }
 
 impl RelationCardinality {
@@ -91,85 +210,327 @@ impl RelationCardinality {
                 (multiple_amount as u8 - 1) as char,
             ),
             Self::Exact(n) => (n.to_string(), multiple_amount),
+            Self::Between(min, max) => (format!("{}..{}", min, max), multiple_amount),
+            Self::AtLeast(min) => (
+                format!("{}..{}", min, multiple_amount),
+                (multiple_amount as u8 - 1) as char,
+            ),
+        }
+    }
+
+    /// Whether at most one instance takes part
+    pub fn is_one(&self) -> bool {
+        matches!(self, Self::One | Self::Exact(1) | Self::Between(_, 1))
+    }
+
+    /// The maximum number of instances, `None` when there is no maximum
+    pub fn max(&self) -> Option<usize> {
+        match self {
+            Self::One => Some(1),
+            Self::Multiple | Self::AtLeast(_) => None,
+            Self::Exact(n) | Self::Between(_, n) => Some(*n),
+        }
+    }
+
+    /// The minimum number of instances when the member is required
+    fn min(&self) -> usize {
+        match self {
+            Self::One | Self::Multiple => 1,
+            Self::Exact(n) | Self::Between(n, _) | Self::AtLeast(n) => (*n).max(1),
         }
     }
 }
 
-impl std::convert::From<String> for RelationCardinality {
-    fn from(s: String) -> Self {
-        if s.starts_with("exactly") {
-            Self::Exact(s["exactly".len()..(s.len() - 1)].parse().unwrap())
+impl std::fmt::Display for RelationCardinality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::One => write!(f, "one"),
+            Self::Multiple => write!(f, "multiple"),
+            Self::Exact(n) => write!(f, "exactly({})", n),
+            Self::Between(min, max) => write!(f, "between({}, {})", min, max),
+            Self::AtLeast(n) => write!(f, "at_least({})", n),
+        }
+    }
+}
+
+impl std::convert::TryFrom<(String, Span)> for RelationCardinality {
+    type Error = ConversionError;
+    fn try_from((s, span): (String, Span)) -> Result<Self, Self::Error> {
+        if let Some(amount) = s.strip_prefix("exactly(").and_then(|s| s.strip_suffix(')')) {
+            match parse_number(amount, &span)? {
+                0 => Err(ConversionError::ZeroCardinality(span)),
+                n => Ok(Self::Exact(n)),
+            }
+        } else if let Some(bounds) = s.strip_prefix("between(").and_then(|s| s.strip_suffix(')')) {
+            let (min, max) = bounds.split_once(',').unwrap();
+            match (
+                parse_number(min.trim(), &span)?,
+                parse_number(max.trim(), &span)?,
+            ) {
+                (min, max) if min > max || max == 0 => {
+                    Err(ConversionError::ImpossibleCardinality(s, span))
+                }
+                (min, max) => Ok(Self::Between(min, max)),
+            }
+        } else if let Some(min) = s
+            .strip_prefix("at_least(")
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            Ok(Self::AtLeast(parse_number(min.trim(), &span)?))
         } else {
             match &s[..] {
-                "one" => Self::One,
-                "multiple" => Self::Multiple,
-                _ => unreachable!(),
+                "one" => Ok(Self::One),
+                "multiple" => Ok(Self::Multiple),
+                _ => Err(ConversionError::UnknownCardinality(s, span)),
             }
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum RelationOptionality {
     Optional,
     Required,
 }
 
-impl std::convert::From<String> for RelationOptionality {
-    fn from(s: String) -> Self {
+impl std::fmt::Display for RelationOptionality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Optional => write!(f, "optional"),
+            Self::Required => write!(f, "required"),
+        }
+    }
+}
+
+impl std::convert::TryFrom<(String, Span)> for RelationOptionality {
+    type Error = ConversionError;
+    fn try_from((s, span): (String, Span)) -> Result<Self, Self::Error> {
         match &s[..] {
-            "optional" => Self::Optional,
-            "required" => Self::Required,
-            _ => unreachable!(),
+            "optional" => Ok(Self::Optional),
+            "required" => Ok(Self::Required),
+            _ => Err(ConversionError::UnknownOptionality(s, span)),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RelationMember {
     pub cardinality: RelationCardinality,
     pub optionality: RelationOptionality,
     pub entity: Ident,
+    /// The role of the entity in the relation, e.g. `mother` in a relation between persons
+    pub role: Option<Ident>,
+    pub span: Span,
 }
 
-impl std::convert::From<(String, String, String)> for RelationMember {
-    fn from((cardinality, optionality, entity): (String, String, String)) -> Self {
-        Self {
-            cardinality: cardinality.into(),
-            optionality: optionality.into(),
+impl RelationMember {
+    /// The role of the member, or the entity when it has no role
+    pub fn name(&self) -> Ident {
+        self.role.clone().unwrap_or_else(|| self.entity.clone())
+    }
+
+    /// The minimum and maximum number of instances, `None` when there is no maximum
+    pub fn bounds(&self) -> (usize, Option<usize>) {
+        let min = match self.optionality {
+            RelationOptionality::Optional => 0,
+            RelationOptionality::Required => self.cardinality.min(),
+        };
+        (min, self.cardinality.max())
+    }
+}
+
+impl std::fmt::Display for RelationMember {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.cardinality, self.optionality, self.entity
+        )?;
+        if let Some(role) = &self.role {
+            write!(f, " as {}", role)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::convert::TryFrom<(String, String, String, Option<String>, Span)> for RelationMember {
+    type Error = ConversionError;
+    fn try_from(
+        (cardinality, optionality, entity, role, span): (
+            String,
+            String,
+            String,
+            Option<String>,
+            Span,
+        ),
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            cardinality: (cardinality, span.clone()).try_into()?,
+            optionality: (optionality, span.clone()).try_into()?,
             entity: entity.into(),
+            role: role.map(|r| r.into()),
+            span,
+        })
+    }
+}
+
+/// What happens to a foreign key when the row it refers to is deleted or updated
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ReferentialAction {
+    Cascade,
+    SetNull,
+    Restrict,
+}
+
+impl std::fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cascade => write!(f, "cascade"),
+            Self::SetNull => write!(f, "set null"),
+            Self::Restrict => write!(f, "restrict"),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl std::convert::TryFrom<(String, Span)> for ReferentialAction {
+    type Error = ConversionError;
+    fn try_from((s, span): (String, Span)) -> Result<Self, Self::Error> {
+        let words: Vec<_> = s.split_whitespace().collect();
+        match &words[..] {
+            ["cascade"] => Ok(Self::Cascade),
+            ["set", "null"] => Ok(Self::SetNull),
+            ["restrict"] => Ok(Self::Restrict),
+            _ => Err(ConversionError::UnknownReferentialAction(s, span)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ForeignKey {
     pub attribute_names: Vec<Ident>,
     pub relation: Ident,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
+    pub span: Span,
 }
 
-impl std::convert::From<(Vec<String>, String)> for ForeignKey {
-    fn from((attrs, relation): (Vec<String>, String)) -> Self {
-        Self {
+impl std::fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = self.attribute_names.iter().map(|n| &n.0[..]).collect();
+        write!(f, "foreign key {} for {}", names.join(", "), self.relation)?;
+        if let Some(action) = &self.on_delete {
+            write!(f, " on delete {}", action)?;
+        }
+        if let Some(action) = &self.on_update {
+            write!(f, " on update {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// (attribute names, relation, on delete, on update, span)
+type ParsedForeignKey = (Vec<String>, String, Option<String>, Option<String>, Span);
+
+impl std::convert::TryFrom<ParsedForeignKey> for ForeignKey {
+    type Error = ConversionError;
+    fn try_from(
+        (attrs, relation, on_delete, on_update, span): ParsedForeignKey,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
             attribute_names: attrs.into_iter().map(|a| a.into()).collect(),
             relation: relation.into(),
-        }
+            on_delete: on_delete
+                .map(|a| (a, span.clone()).try_into())
+                .transpose()?,
+            on_update: on_update
+                .map(|a| (a, span.clone()).try_into())
+                .transpose()?,
+            span,
+        })
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Expr {
-    /// Matches an entity with attributes
-    Entity(Ident, Vec<Attribute>),
-    /// Matches a relation with an optional name, members and attributes
-    Relation(Ident, Option<String>, Vec<RelationMember>, Vec<Attribute>),
+    /// Matches an entity with attributes, weak when it is identified by an owner
+    Entity(Ident, Vec<Attribute>, bool, Span),
+    /// Matches a relation with an optional name, members and attributes, identifying when it
+    /// identifies a weak entity
+    Relation(
+        Ident,
+        Option<String>,
+        Vec<RelationMember>,
+        Vec<Attribute>,
+        bool,
+        Span,
+    ),
     /// Matches a table with a name based on an entity with some foreign key settings
-    EntityTable(Ident, Ident, Vec<ForeignKey>),
+    EntityTable(Ident, Ident, Vec<ForeignKey>, Span),
     /// Matches a table with a name based on a relation
-    RelationTable(Ident, Ident),
+    RelationTable(Ident, Ident, Span),
+    /// Matches an import of another erd-script file, relative to the importing file
+    Import(String, Span),
+}
+
+impl Expr {
+    /// The span of the name of the entity, relation or table or of the imported path
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::Entity(_, _, _, span)
+            | Self::Relation(_, _, _, _, _, span)
+            | Self::EntityTable(_, _, _, span)
+            | Self::RelationTable(_, _, span)
+            | Self::Import(_, span) => span,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Writes the expression as erd-script
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Entity(name, attributes, weak, _) => {
+                if *weak {
+                    write!(f, "weak ")?;
+                }
+                write!(f, "entity {}", name)?;
+                for attribute in attributes.iter() {
+                    write!(f, "\n  {}", attribute)?;
+                }
+                Ok(())
+            }
+            Self::Relation(name, label, members, attributes, identifying, _) => {
+                if *identifying {
+                    write!(f, "identifying ")?;
+                }
+                write!(f, "relation {}", name)?;
+                if let Some(label) = label {
+                    write!(f, "({})", label)?;
+                }
+                for member in members.iter() {
+                    write!(f, "\n  {}", member)?;
+                }
+                for attribute in attributes.iter() {
+                    write!(f, "\n  {}", attribute)?;
+                }
+                Ok(())
+            }
+            Self::EntityTable(name, entity, foreign_keys, _) => {
+                write!(f, "table {} from entity {}", name, entity)?;
+                for foreign_key in foreign_keys.iter() {
+                    write!(f, "\n{}", foreign_key)?;
+                }
+                Ok(())
+            }
+            Self::RelationTable(name, relation, _) => {
+                write!(f, "table {} from relation {}", name, relation)
+            }
+            Self::Import(path, _) => write!(f, "import \"{}\"", path),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum DataType {
     Integer,
     SmallInteger,
@@ -191,42 +552,128 @@ pub enum DataType {
     Enum(Vec<String>),
 }
 
-impl std::convert::From<String> for DataType {
-    fn from(s: String) -> Self {
-        if s.starts_with("varchar") {
-            Self::Varchar(s["varchar(".len()..(s.len() - 1)].parse().unwrap())
-        } else if s.starts_with("varbinary") {
-            Self::Varbinary(s["varbinary(".len()..(s.len() - 1)].parse().unwrap())
-        } else if s.starts_with("decimal") {
-            let mut comma = s.split(",");
-            let m = comma.next().unwrap()["decimal(".len()..].parse().unwrap();
-            let part2 = comma.next().unwrap();
-            let d = part2[..(part2.len() - 1)].trim().parse().unwrap();
-
-            Self::Decimal(m, d)
-        } else if s.starts_with("enum(") {
-            let part = &s["enum(".len()..(s.len() - 1)];
-            let elements: Vec<String> = part.split(",").map(|x| x.trim().to_string()).collect();
-            Self::Enum(elements)
+impl std::fmt::Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer => write!(f, "integer"),
+            Self::SmallInteger => write!(f, "smallinteger"),
+            Self::AutoIncrement => write!(f, "autoincrement"),
+            Self::Float => write!(f, "float"),
+            Self::Boolean => write!(f, "boolean"),
+            Self::Date => write!(f, "date"),
+            Self::Time => write!(f, "time"),
+            Self::DateTime => write!(f, "datetime"),
+            Self::Uuid => write!(f, "uuid"),
+            Self::Text => write!(f, "text"),
+            Self::Blob => write!(f, "blob"),
+            Self::Varchar(n) => write!(f, "varchar({})", n),
+            Self::Varbinary(n) => write!(f, "varbinary({})", n),
+            Self::Decimal(m, d) => write!(f, "decimal({}, {})", m, d),
+            Self::Enum(items) => write!(f, "enum({})", items.join(", ")),
+        }
+    }
+}
+
+impl std::convert::TryFrom<(String, Span)> for DataType {
+    type Error = ConversionError;
+    fn try_from((s, span): (String, Span)) -> Result<Self, Self::Error> {
+        let arguments = |prefix: &str| {
+            s.strip_prefix(prefix)
+                .and_then(|s| s.strip_suffix(')'))
+                .map(|s| s.split(',').map(|x| x.trim()).collect::<Vec<_>>())
+        };
+        if let Some([n]) = arguments("varchar(").as_deref() {
+            Ok(Self::Varchar(parse_number(n, &span)?))
+        } else if let Some([n]) = arguments("varbinary(").as_deref() {
+            Ok(Self::Varbinary(parse_number(n, &span)?))
+        } else if let Some([m, d]) = arguments("decimal(").as_deref() {
+            Ok(Self::Decimal(
+                parse_number(m, &span)?,
+                parse_number(d, &span)?,
+            ))
+        } else if let Some(elements) = arguments("enum(") {
+            Ok(Self::Enum(
+                elements.into_iter().map(|x| x.to_string()).collect(),
+            ))
         } else {
             match &s[..] {
-                "uuid" => Self::Uuid,
-                "text" => Self::Text,
-                "blob" => Self::Blob,
-                "integer" => Self::Integer,
-                "smallinteger" => Self::SmallInteger,
-                "autoincrement" => Self::AutoIncrement,
-                "float" => Self::Float,
-                "boolean" => Self::Boolean,
-                "date" => Self::Date,
-                "time" => Self::Time,
-                "datetime" => Self::DateTime,
-                _ => unreachable!(),
+                "uuid" => Ok(Self::Uuid),
+                "text" => Ok(Self::Text),
+                "blob" => Ok(Self::Blob),
+                "integer" => Ok(Self::Integer),
+                "smallinteger" => Ok(Self::SmallInteger),
+                "autoincrement" => Ok(Self::AutoIncrement),
+                "float" => Ok(Self::Float),
+                "boolean" => Ok(Self::Boolean),
+                "date" => Ok(Self::Date),
+                "time" => Ok(Self::Time),
+                "datetime" => Ok(Self::DateTime),
+                _ => Err(ConversionError::UnknownDataType(s, span)),
             }
         }
     }
 }
 
+fn parse_number(n: &str, span: &Span) -> Result<usize, ConversionError> {
+    n.parse()
+        .map_err(|_| ConversionError::NumberOutOfRange(n.to_string(), span.clone()))
+}
+
+/// Errors while converting the parsed script to the ast
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ConversionError {
+    NumberOutOfRange(String, Span),
+    ZeroCardinality(Span),
+    UnknownDataType(String, Span),
+    UnknownCardinality(String, Span),
+    UnknownOptionality(String, Span),
+    UnknownAttributeType(String, Span),
+    UnknownAttributeModifier(String, Span),
+    UnknownReferentialAction(String, Span),
+    ImpossibleCardinality(String, Span),
+}
+
+impl ConversionError {
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::NumberOutOfRange(_, s)
+            | Self::ZeroCardinality(s)
+            | Self::UnknownDataType(_, s)
+            | Self::UnknownCardinality(_, s)
+            | Self::UnknownOptionality(_, s)
+            | Self::UnknownAttributeType(_, s)
+            | Self::UnknownAttributeModifier(_, s)
+            | Self::UnknownReferentialAction(_, s)
+            | Self::ImpossibleCardinality(_, s) => s,
+        }
+    }
+
+    /// The error message without its location
+    pub fn message(&self) -> String {
+        match self {
+            Self::NumberOutOfRange(n, _) => format!("The number {} is too large.", n),
+            Self::ZeroCardinality(_) => "A cardinality of exactly(0) is not possible.".to_string(),
+            Self::UnknownDataType(d, _) => format!("Unknown datatype {}.", d),
+            Self::UnknownCardinality(c, _) => format!("Unknown cardinality {}.", c),
+            Self::UnknownOptionality(o, _) => format!("Unknown optionality {}.", o),
+            Self::UnknownAttributeType(t, _) => format!("Unknown attribute type {}.", t),
+            Self::UnknownAttributeModifier(m, _) => format!("Unknown attribute modifier {}.", m),
+            Self::UnknownReferentialAction(a, _) => format!("Unknown referential action {}.", a),
+            Self::ImpossibleCardinality(c, _) => format!("A cardinality of {} is not possible.", c),
+        }
+    }
+}
+
+impl std::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.span().is_empty() {
+            write!(f, "{}", self.message())
+        } else {
+            write!(f, "{}: {}", self.span(), self.message())
+        }
+    }
+}
+
 impl DataType {
     pub fn foreign_key_type(&self) -> DataType {
         match self {
@@ -0,0 +1,379 @@
+use crate::ast::{ConversionError, Ident, Span};
+use crate::erd::ERDError;
+use crate::parser::{ConsumeError, Rule};
+use crate::physical::PhysicalError;
+use crate::project::ImportError;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// An error together with everything needed to show it to the user
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: String,
+    pub message: String,
+    pub span: Option<Span>,
+    pub help: Option<String>,
+}
+
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self) -> Diagnostic;
+}
+
+impl Diagnostic {
+    fn new(code: &str, message: String, span: Option<Span>, help: Option<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message,
+            span: span.filter(|s| !s.is_empty()),
+            help,
+        }
+    }
+}
+
+impl ToDiagnostic for pest::error::Error<Rule> {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let (start, end) = match self.location {
+            pest::error::InputLocation::Pos(p) => (p, p),
+            pest::error::InputLocation::Span((s, e)) => (s, e),
+        };
+        let (line, column) = match self.line_col {
+            pest::error::LineColLocation::Pos(p) => p,
+            pest::error::LineColLocation::Span(p, _) => p,
+        };
+        let span = Span {
+            file: self.path().map(|p| p.to_string()),
+            start,
+            end,
+            line,
+            column,
+        };
+        Diagnostic::new(
+            "E0001",
+            self.variant.message().to_string(),
+            Some(span),
+            None,
+        )
+    }
+}
+
+impl ConsumeError {
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Self::ERDParseError(errors) => errors.iter().map(|e| e.to_diagnostic()).collect(),
+            Self::ConversionError(errors) => errors.iter().map(|e| e.to_diagnostic()).collect(),
+        }
+    }
+}
+
+impl ImportError {
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Self::ParsingError(e) => e.to_diagnostics(),
+            Self::ReadError(_, _, span) => {
+                vec![Diagnostic::new(
+                    "E0010",
+                    self.to_string(),
+                    span.clone(),
+                    None,
+                )]
+            }
+            Self::ImportCycle(_, span) => vec![Diagnostic::new(
+                "E0011",
+                self.to_string(),
+                Some(span.clone()),
+                Some("remove one of the imports in the cycle".to_string()),
+            )],
+        }
+    }
+}
+
+impl ToDiagnostic for ConversionError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let (code, help) = match self {
+            Self::NumberOutOfRange(_, _) => ("E0002", None),
+            Self::ZeroCardinality(_) => (
+                "E0003",
+                Some("use `optional` for members that don't need to participate".to_string()),
+            ),
+            Self::UnknownDataType(_, _) => ("E0004", None),
+            Self::UnknownCardinality(_, _) => ("E0005", None),
+            Self::UnknownOptionality(_, _) => ("E0006", None),
+            Self::UnknownAttributeType(_, _) => ("E0007", None),
+            Self::UnknownAttributeModifier(_, _) => ("E0008", None),
+            Self::UnknownReferentialAction(_, _) => ("E0009", None),
+            Self::ImpossibleCardinality(_, _) => (
+                "E0012",
+                Some("the maximum should be at least 1 and at least the minimum".to_string()),
+            ),
+        };
+        Diagnostic::new(code, self.message(), Some(self.span().clone()), help)
+    }
+}
+
+impl ToDiagnostic for ERDError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let (code, help) = match self {
+            Self::DuplicateIdent(i, first, _) => (
+                "E0101",
+                Some(format!("`{}` is first defined at {}", i, first)),
+            ),
+            Self::DuplicateAttributeInEntity(_, _, _) => ("E0102", None),
+            Self::DuplicateAttributeInRelation(_, _, _) => ("E0103", None),
+            Self::UnknownEntityInRelation(_, _, suggestion, _) => (
+                "E0104",
+                suggestion
+                    .as_ref()
+                    .map(|s| format!("did you mean entity `{}`?", s)),
+            ),
+            Self::MissingRoleInRelation(_, _, _) => (
+                "E0105",
+                Some("give every occurrence a role with `as <role>`".to_string()),
+            ),
+            Self::DuplicateRoleInRelation(_, _, _) => ("E0106", None),
+            Self::InvalidIdentifyingRelation(_, _) => (
+                "E0107",
+                Some("relate a weak entity to `one required <owner>`".to_string()),
+            ),
+            Self::UnidentifiedWeakEntity(_, _) => ("E0108", None),
+            Self::CyclicWeakEntity(_, _) => ("E0109", None),
+        };
+        Diagnostic::new(code, self.message(), Some(self.span().clone()), help)
+    }
+}
+
+impl ToDiagnostic for PhysicalError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let (code, help) = match self {
+            Self::DuplicateTableName(_, _) => ("E0201", None),
+            Self::DuplicateColumnNameInTable(_, _, _) => (
+                "E0202",
+                Some("choose other names for the foreign key columns".to_string()),
+            ),
+            Self::UnknownEntityOrRelationInTable(_, _, suggestion, _) => (
+                "E0203",
+                suggestion
+                    .as_ref()
+                    .map(|s| format!("did you mean `{}`?", s)),
+            ),
+            Self::ConvertedMoreThanOnce(_, _) => ("E0204", None),
+            Self::ForgottenEntityOrRelation(e, _) => (
+                "E0205",
+                Some(format!(
+                    "add a `table` for {} or a `foreign key` for it in another table",
+                    e
+                )),
+            ),
+            Self::ForeignKeyToEntityInTable(_, _, _) => (
+                "E0207",
+                Some("use `foreign key <columns> for <relation>`".to_string()),
+            ),
+            Self::ImpossibleForeignKey(r, _, _) => (
+                "E0208",
+                Some(format!("use `table <name> from relation {}` instead", r)),
+            ),
+            Self::MissingDataTypeFromAttributeInEntityOrRelation(_, _, _) => (
+                "E0209",
+                Some("add a datatype with `type <datatype>`".to_string()),
+            ),
+            Self::DefinitionInPhysicalMapping(_, _) => (
+                "E0210",
+                Some("move the definition to the ERD file".to_string()),
+            ),
+            Self::SetNullOnRequiredForeignKey(r, _, _) => (
+                "E0211",
+                Some(format!(
+                    "use `cascade` or `restrict`, or make the member of {} optional",
+                    r
+                )),
+            ),
+            Self::EnumWithoutValues(_, _, _) => ("E0212", None),
+            Self::WrongAmountOfForeignKeyColumns(_, _, key, _) => (
+                "E0213",
+                Some(format!(
+                    "use one name for every column of the referenced key: {}",
+                    key.iter()
+                        .map(|k| k.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            ),
+        };
+        Diagnostic::new(code, self.message(), Some(self.span().clone()), help)
+    }
+}
+
+/// Finds the candidate that is closest to `name`, if it is close enough to be a typo
+pub fn suggest<'a>(name: &Ident, candidates: impl Iterator<Item = &'a Ident>) -> Option<Ident> {
+    let max_distance = std::cmp::max(1, name.0.chars().count() / 3);
+    candidates
+        .map(|c| (edit_distance(&name.0, &c.0), c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by(|(d1, c1), (d2, c2)| d1.cmp(d2).then_with(|| c1.0.cmp(&c2.0)))
+        .map(|(_, c)| c.clone())
+}
+
+// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, code: &str, s: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, s)
+        } else {
+            s.to_string()
+        }
+    }
+    fn error(&self, s: &str) -> String {
+        self.paint("1;31", s)
+    }
+    fn gutter(&self, s: &str) -> String {
+        self.paint("1;34", s)
+    }
+    fn bold(&self, s: &str) -> String {
+        self.paint("1", s)
+    }
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic in the style of rustc, `source` is the content of the file
+    /// the span refers to
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let style = Style { color };
+        let mut s = String::new();
+        let _ = self.write_rendered(&mut s, source, &style);
+        s
+    }
+
+    fn write_rendered(&self, s: &mut String, source: &str, style: &Style) -> std::fmt::Result {
+        writeln!(
+            s,
+            "{}{}",
+            style.error(&format!("error[{}]", self.code)),
+            style.bold(&format!(": {}", self.message))
+        )?;
+        let mut gutter_width = 1;
+        if let Some(span) = &self.span {
+            let line_number = span.line.to_string();
+            gutter_width = line_number.len() + 1;
+            let padding = " ".repeat(gutter_width);
+            writeln!(
+                s,
+                "{}{} {}",
+                " ".repeat(gutter_width - 1),
+                style.gutter("-->"),
+                span
+            )?;
+
+            let line_start = source[..span.start.min(source.len())]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            let line_end = source[line_start..]
+                .find('\n')
+                .map(|i| line_start + i)
+                .unwrap_or(source.len());
+            let line = source[line_start..line_end].trim_end_matches('\r');
+            let start = span.start.clamp(line_start, line_start + line.len());
+            let end = span.end.clamp(start, line_start + line.len());
+            // Keep tabs so the carets line up with the source line
+            let indent: String = source[line_start..start]
+                .chars()
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let width = std::cmp::max(1, source[start..end].chars().count());
+
+            writeln!(s, "{}{}", padding, style.gutter("|"))?;
+            writeln!(
+                s,
+                "{} {}",
+                style.gutter(&format!("{:<w$}|", line_number, w = gutter_width)),
+                line
+            )?;
+            writeln!(
+                s,
+                "{}{} {}{}",
+                padding,
+                style.gutter("|"),
+                indent,
+                style.error(&"^".repeat(width))
+            )?;
+        }
+        if let Some(help) = &self.help {
+            writeln!(
+                s,
+                "{}{} {}",
+                " ".repeat(gutter_width),
+                style.gutter("="),
+                style.bold(&format!("help: {}", help))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::erd::{ERDFromScriptError, ERD};
+
+    #[test]
+    fn render_unknown_entity() {
+        let source = "entity Student\n\nrelation Follows\n  one required Studnet\n  multiple optional Student\n";
+        let errors = match ERD::from_script(source) {
+            Err(ERDFromScriptError::ERDError(errors)) => errors,
+            other => panic!("Expected ERD errors, got {:?}", other),
+        };
+        assert_eq!(
+            errors[0].to_diagnostic().render(source, false),
+            "error[E0104]: Unknown entity Studnet in relation Follows.
+ --> 4:3
+  |
+4 |   one required Studnet
+  |   ^^^^^^^^^^^^^^^^^^^^
+  = help: did you mean entity `Student`?
+"
+        );
+    }
+
+    #[test]
+    fn impossible_cardinality_has_its_own_code() {
+        let source = "entity Student\n\nrelation Follows\n  between(3, 1) required Student\n  multiple optional Student\n";
+        let errors = match ERD::from_script(source) {
+            Err(ERDFromScriptError::ParsingError(errors)) => errors,
+            other => panic!("Expected parsing errors, got {:?}", other),
+        };
+        let diagnostics: Vec<_> = errors.iter().flat_map(|e| e.to_diagnostics()).collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "E0012");
+    }
+
+    #[test]
+    fn suggest_closest() {
+        let candidates: Vec<Ident> =
+            vec!["Student".to_string().into(), "Course".to_string().into()];
+        assert_eq!(
+            suggest(&"Studnet".to_string().into(), candidates.iter()),
+            Some("Student".to_string().into())
+        );
+        assert_eq!(
+            suggest(&"Teacher".to_string().into(), candidates.iter()),
+            None
+        );
+    }
+}
@@ -20,7 +20,7 @@ impl fmt::Display for Graph {
         if let Some(id) = &self.id {
             write!(f, "{} ", id)?;
         }
-        write!(f, "{{\n")?;
+        writeln!(f, "{{")?;
         for statement in &self.statements {
             writeln!(f, "{}", statement)?;
         }
@@ -1,10 +1,12 @@
-use crate::ast::{Attribute, AttributeType, Expr, Ident, RelationMember, RelationOptionality};
+use crate::ast::{
+    Attribute, AttributeType, DataType, Expr, Ident, RelationMember, RelationOptionality, Span,
+};
 use crate::dot;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ERD {
     entities: Vec<Entity>,
     relations: Vec<Relation>,
@@ -12,14 +14,16 @@ pub struct ERD {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ERDFromScriptError {
-    ParsingError(crate::parser::ConsumeError),
+    ParsingError(Vec<crate::parser::ConsumeError>),
     ERDError(Vec<ERDError>),
 }
 
 impl ERD {
     pub fn from_script(content: &str) -> Result<ERD, ERDFromScriptError> {
-        let pairs = crate::parser::parse_as_erd(&content).map_err(|e| {
-            ERDFromScriptError::ParsingError(crate::parser::ConsumeError::ERDParseError(vec![e]))
+        let pairs = crate::parser::parse_as_erd(content).map_err(|e| {
+            ERDFromScriptError::ParsingError(vec![crate::parser::ConsumeError::ERDParseError(
+                vec![e],
+            )])
         })?;
         let asts =
             crate::parser::consume_expressions(pairs).map_err(ERDFromScriptError::ParsingError)?;
@@ -30,84 +34,188 @@ impl ERD {
 impl ERD {
     fn validate(&self) -> Vec<ERDError> {
         let mut errors = Vec::new();
-        let mut entity_names: HashSet<Ident> = HashSet::new();
+        let mut entity_names: HashMap<Ident, Span> = HashMap::new();
         for e in self.entities.iter() {
-            if entity_names.contains(&e.name) {
-                errors.push(ERDError::DuplicateIdent(e.name.clone()))
+            if let Some(first) = entity_names.get(&e.name) {
+                errors.push(ERDError::DuplicateIdent(
+                    e.name.clone(),
+                    first.clone(),
+                    e.span.clone(),
+                ))
             } else {
-                entity_names.insert(e.name.clone());
+                entity_names.insert(e.name.clone(), e.span.clone());
             }
             let mut entity_attributes = HashSet::new();
             for attribute in e.attributes.iter() {
-                if entity_attributes.contains(attribute) {
+                if entity_attributes.contains(&attribute.ident) {
                     errors.push(ERDError::DuplicateAttributeInEntity(
                         attribute.get_ident(),
                         e.name.clone(),
+                        attribute.span.clone(),
                     ));
                 } else {
-                    entity_attributes.insert(attribute.clone());
+                    entity_attributes.insert(attribute.get_ident());
                 }
             }
         }
 
-        let mut relation_names: HashSet<Ident> = HashSet::new();
+        let mut relation_names: HashMap<Ident, Span> = HashMap::new();
         for r in self.relations.iter() {
-            if entity_names.contains(&r.name) || relation_names.contains(&r.name) {
-                errors.push(ERDError::DuplicateIdent(r.name.clone()))
+            if let Some(first) = entity_names
+                .get(&r.name)
+                .or_else(|| relation_names.get(&r.name))
+            {
+                errors.push(ERDError::DuplicateIdent(
+                    r.name.clone(),
+                    first.clone(),
+                    r.span.clone(),
+                ))
             } else {
-                relation_names.insert(r.name.clone());
+                relation_names.insert(r.name.clone(), r.span.clone());
             }
             for member in r.members.iter() {
-                if !entity_names.contains(&member.entity) {
+                if !entity_names.contains_key(&member.entity) {
                     errors.push(ERDError::UnknownEntityInRelation(
                         member.entity.clone(),
                         r.name.clone(),
+                        crate::diagnostic::suggest(&member.entity, entity_names.keys()),
+                        member.span.clone(),
                     ));
                 }
             }
+            let mut roles = HashSet::new();
+            for member in r.members.iter() {
+                match &member.role {
+                    Some(role) if !roles.insert(role.clone()) => {
+                        errors.push(ERDError::DuplicateRoleInRelation(
+                            role.clone(),
+                            r.name.clone(),
+                            member.span.clone(),
+                        ));
+                    }
+                    Some(_) => (),
+                    None if r
+                        .members
+                        .iter()
+                        .filter(|m| m.entity == member.entity)
+                        .count()
+                        > 1 =>
+                    {
+                        errors.push(ERDError::MissingRoleInRelation(
+                            member.entity.clone(),
+                            r.name.clone(),
+                            member.span.clone(),
+                        ));
+                    }
+                    None => (),
+                }
+            }
             let mut relation_attributes = HashSet::new();
             for attribute in r.attributes.iter() {
-                if relation_attributes.contains(attribute) {
+                if relation_attributes.contains(&attribute.ident) {
                     errors.push(ERDError::DuplicateAttributeInRelation(
                         attribute.get_ident(),
                         r.name.clone(),
+                        attribute.span.clone(),
                     ));
                 } else {
-                    relation_attributes.insert(attribute.clone());
+                    relation_attributes.insert(attribute.get_ident());
                 }
             }
         }
 
+        for r in self.relations.iter().filter(|r| r.identifying) {
+            let identifies_weak = r
+                .members
+                .iter()
+                .any(|m| self.is_weak(&m.entity) && r.owner_of(&m.entity).is_some());
+            if !identifies_weak {
+                errors.push(ERDError::InvalidIdentifyingRelation(
+                    r.name.clone(),
+                    r.span.clone(),
+                ));
+            }
+        }
+        for e in self.entities.iter().filter(|e| e.weak) {
+            let owners = self
+                .relations
+                .iter()
+                .filter(|r| r.owner_of(&e.name).is_some())
+                .count();
+            if owners != 1 {
+                errors.push(ERDError::UnidentifiedWeakEntity(
+                    e.name.clone(),
+                    e.span.clone(),
+                ));
+                continue;
+            }
+            // Following the owners has to end at a strong entity
+            let mut visited = HashSet::new();
+            let mut current = e.name.clone();
+            while let Some((_, owner)) = self.get_owner(&current) {
+                if owner.entity == e.name {
+                    errors.push(ERDError::CyclicWeakEntity(e.name.clone(), e.span.clone()));
+                    break;
+                }
+                if !visited.insert(owner.entity.clone()) {
+                    break;
+                }
+                current = owner.entity;
+            }
+        }
+
         errors
     }
 
-    // Vec<(Relation/Entity, attribute)>
-    pub fn get_missing_datatypes(&self) -> Vec<(Ident, Ident)> {
+    // Vec<(Relation/Entity, attribute, attribute span)>
+    pub fn get_missing_datatypes(&self) -> Vec<(Ident, Ident, Span)> {
         self.entities
             .iter()
             .flat_map(|e| {
                 e.attributes
                     .iter()
                     .filter(|a| a.datatype.is_none())
-                    .map(move |a| (e.name.to_owned(), a.ident.to_owned()))
+                    .map(move |a| (e.name.to_owned(), a.ident.to_owned(), a.span.to_owned()))
             })
             .chain(self.relations.iter().flat_map(|r| {
                 r.attributes
                     .iter()
                     .filter(|a| a.datatype.is_none())
-                    .map(move |a| (r.name.to_owned(), a.ident.to_owned()))
+                    .map(move |a| (r.name.to_owned(), a.ident.to_owned(), a.span.to_owned()))
             }))
             .collect()
     }
 }
 
 impl ERD {
+    /// The attributes with an enum datatype without values, like
+    /// Vec<(Relation/Entity, attribute, attribute span)>
+    pub fn get_empty_enums(&self) -> Vec<(Ident, Ident, Span)> {
+        let is_empty =
+            |a: &&Attribute| matches!(&a.datatype, Some(DataType::Enum(o)) if o.is_empty());
+        self.entities
+            .iter()
+            .flat_map(|e| {
+                e.attributes
+                    .iter()
+                    .filter(is_empty)
+                    .map(move |a| (e.name.to_owned(), a.ident.to_owned(), a.span.to_owned()))
+            })
+            .chain(self.relations.iter().flat_map(|r| {
+                r.attributes
+                    .iter()
+                    .filter(is_empty)
+                    .map(move |a| (r.name.to_owned(), a.ident.to_owned(), a.span.to_owned()))
+            }))
+            .collect()
+    }
+
     pub fn has_entity(&self, name: Ident) -> bool {
-        self.entities.iter().find(|e| e.name == name).is_some()
+        self.entities.iter().any(|e| e.name == name)
     }
 
     pub fn has_relation(&self, name: Ident) -> bool {
-        self.relations.iter().find(|e| e.name == name).is_some()
+        self.relations.iter().any(|e| e.name == name)
     }
 
     pub fn get_relation_attributes(&self, name: Ident) -> Vec<Attribute> {
@@ -146,6 +254,44 @@ impl ERD {
             .collect()
     }
 
+    pub fn is_weak(&self, name: &Ident) -> bool {
+        self.entities.iter().any(|e| &e.name == name && e.weak)
+    }
+
+    /// The identifying relation and the owner member of the weak entity with this name
+    pub fn get_owner(&self, name: &Ident) -> Option<(Relation, RelationMember)> {
+        if !self.is_weak(name) {
+            return None;
+        }
+        self.relations
+            .iter()
+            .find_map(|r| r.owner_of(name).map(|o| (r.clone(), o.clone())))
+    }
+
+    /// The key of the owner of a weak entity, named after the role or entity of the owner.
+    /// It is empty for other entities.
+    pub fn get_owner_key(&self, name: &Ident) -> Vec<Attribute> {
+        self.get_owner(name)
+            .map(|(_, owner)| {
+                self.get_entity_key(owner.entity.clone())
+                    .into_iter()
+                    .map(|a| Attribute {
+                        datatype: a.datatype.as_ref().map(|d| d.foreign_key_type()),
+                        modifiers: Vec::new(),
+                        ..a.renamed(format!("{}_{}", owner.name(), a.ident).into())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The full key of an entity, a weak entity adds its partial key to the key of its owner
+    pub fn get_entity_key(&self, name: Ident) -> Vec<Attribute> {
+        let mut key = self.get_owner_key(&name);
+        key.extend(self.get_entity_ids(name));
+        key
+    }
+
     pub fn get_idents(&self) -> HashSet<Ident> {
         self.entities
             .iter()
@@ -162,29 +308,50 @@ impl ERD {
     }
 
     pub fn get_relation_attribute(&self, name: Ident, attribute: Ident) -> Option<Attribute> {
-        self.get_relation(name)
-            .map(|r| {
-                r.attributes
-                    .clone()
-                    .into_iter()
-                    .find(|a| a.get_ident() == attribute)
+        self.get_relation(name).and_then(|r| {
+            r.attributes
+                .clone()
+                .into_iter()
+                .find(|a| a.get_ident() == attribute)
+        })
+    }
+
+    /// The span of the name of the entity or relation with this name
+    pub fn get_span(&self, name: Ident) -> Option<Span> {
+        self.entities
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| e.span.clone())
+            .or_else(|| {
+                self.relations
+                    .iter()
+                    .find(|r| r.name == name)
+                    .map(|r| r.span.clone())
             })
-            .flatten()
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Entity {
     name: Ident,
     attributes: Vec<Attribute>,
+    weak: bool,
+    span: Span,
 }
 
 impl Attribute {
-    fn to_dot_statements(&self, entity: Ident) -> Vec<dot::Statement> {
+    /// The keys of a weak entity are partial keys, they are drawn dashed
+    fn to_dot_statements(&self, entity: Ident, weak: bool) -> Vec<dot::Statement> {
         let mut attributes = vec![dot::AListItem {
             key: "shape".into(),
             value: "ellipse".into(),
         }];
+        if weak && self.get_type() == AttributeType::Key {
+            attributes.push(dot::AListItem {
+                key: "style".into(),
+                value: "dashed".into(),
+            });
+        }
 
         let attribute_name: String = self.get_ident().into();
         attributes.push(dot::AListItem {
@@ -208,13 +375,20 @@ impl Attribute {
 
 impl ToDot for Entity {
     fn to_dot_statements(&self) -> Vec<dot::Statement> {
+        let mut node_attributes = vec![dot::AListItem {
+            key: "shape".into(),
+            value: "box".into(),
+        }];
+        if self.weak {
+            node_attributes.push(dot::AListItem {
+                key: "peripheries".into(),
+                value: "2".into(),
+            });
+        }
         let entity_node = dot::Statement::Node(dot::NodeStatement {
             node: self.name.clone().into(),
             attributes: Some(dot::AttributeList {
-                content: dot::AList(vec![dot::AListItem {
-                    key: "shape".into(),
-                    value: "box".into(),
-                }]),
+                content: dot::AList(node_attributes),
                 tail: Box::new(None),
             }),
         });
@@ -225,7 +399,7 @@ impl ToDot for Entity {
         statements.extend(
             self.attributes
                 .iter()
-                .flat_map(|a| a.to_dot_statements(self.name.clone())),
+                .flat_map(|a| a.to_dot_statements(self.name.clone(), self.weak)),
         );
 
         let entity_name: String = self.name.clone().into();
@@ -252,17 +426,23 @@ impl ToDot for Entity {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Relation {
     name: Ident,
     label: Option<String>,
     members: Vec<RelationMember>,
     attributes: Vec<Attribute>,
+    identifying: bool,
+    span: Span,
 }
 
 impl Relation {
     pub fn name(&self) -> Ident {
-        return self.name.clone();
+        self.name.clone()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span.clone()
     }
 
     pub fn degree(&self) -> usize {
@@ -270,38 +450,77 @@ impl Relation {
     }
 
     pub fn can_work_with_foreign_key(&self, entity: Ident) -> bool {
-        let mut nb_more_than_one = 0;
-        for member in self.members.iter() {
-            match member.cardinality {
-                crate::ast::RelationCardinality::One => (),
-                _ => nb_more_than_one += 1,
-            }
-        }
-        self.degree() == 2
-            && nb_more_than_one < 2
-            && self
-                .members
-                .iter()
-                .find(|m| m.entity != entity)
-                .map(|m| m.cardinality == crate::ast::RelationCardinality::One)
-                .unwrap_or(false)
+        self.degree() == 2 && self.foreign_key_members(&entity).is_some()
     }
 
     pub fn find_other_member(&self, entity: Ident) -> Ident {
+        self.find_other(entity).entity.clone()
+    }
+
+    /// The member that a foreign key in the table of `entity` refers to
+    pub fn find_other(&self, entity: Ident) -> &RelationMember {
+        self.foreign_key_members(&entity).unwrap().1
+    }
+
+    /// The member of `entity` and the member with cardinality one it can refer to. The
+    /// cardinalities decide which is which when a relation is unary, not the order.
+    pub fn foreign_key_members(
+        &self,
+        entity: &Ident,
+    ) -> Option<(&RelationMember, &RelationMember)> {
         self.members
             .iter()
-            .find(|m| m.entity != entity)
-            .map(|e| e.entity.clone())
-            .unwrap()
+            .enumerate()
+            .filter(|(_, m)| &m.entity == entity)
+            .find_map(|(i, own)| {
+                self.members
+                    .iter()
+                    .enumerate()
+                    .find(|(j, other)| i != *j && other.cardinality.is_one())
+                    .map(|(_, other)| (own, other))
+            })
+    }
+
+    /// The owner member of `entity` when this relation identifies it: a binary identifying
+    /// relation where every `entity` belongs to exactly one other entity
+    pub fn owner_of(&self, entity: &Ident) -> Option<&RelationMember> {
+        match &self.members[..] {
+            [a, b] if self.identifying => [(a, b), (b, a)]
+                .into_iter()
+                .find(|(weak, owner)| {
+                    &weak.entity == entity
+                        && &owner.entity != entity
+                        && owner.bounds() == (1, Some(1))
+                })
+                .map(|(_, owner)| owner),
+            _ => None,
+        }
     }
 
     pub fn get_members(&self) -> Vec<Ident> {
         self.members.iter().map(|e| e.entity.clone()).collect()
     }
+
+    /// The members with whether their key is part of the key of the relation. A member with
+    /// cardinality one is determined by the others, so the last such member is left out.
+    pub fn get_members_with_key(&self) -> Vec<(RelationMember, bool)> {
+        let determined = self.members.iter().rposition(|m| m.cardinality.is_one());
+        self.members
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.clone(), Some(i) != determined))
+            .collect()
+    }
 }
 
 impl ToDot for Relation {
     fn to_dot_statements(&self) -> Vec<dot::Statement> {
+        self.to_dot_statements_with(Notation::default())
+    }
+}
+
+impl Relation {
+    fn to_dot_statements_with(&self, notation: Notation) -> Vec<dot::Statement> {
         let relation_name: String = self.name.clone().into();
 
         let relation_label = if let Some(l) = self.label.clone() {
@@ -309,19 +528,26 @@ impl ToDot for Relation {
         } else {
             relation_name.clone()
         };
+        let mut node_attributes = vec![
+            dot::AListItem {
+                key: "shape".into(),
+                value: "diamond".into(),
+            },
+            dot::AListItem {
+                key: "label".into(),
+                value: format!("\"{}\"", relation_label),
+            },
+        ];
+        if self.identifying {
+            node_attributes.push(dot::AListItem {
+                key: "peripheries".into(),
+                value: "2".into(),
+            });
+        }
         let relation_node = dot::Statement::Node(dot::NodeStatement {
             node: relation_name.clone(),
             attributes: Some(dot::AttributeList {
-                content: dot::AList(vec![
-                    dot::AListItem {
-                        key: "shape".into(),
-                        value: "diamond".into(),
-                    },
-                    dot::AListItem {
-                        key: "label".into(),
-                        value: format!("\"{}\"", relation_label),
-                    },
-                ]),
+                content: dot::AList(node_attributes),
                 tail: Box::new(None),
             }),
         });
@@ -332,7 +558,7 @@ impl ToDot for Relation {
         statements.extend(
             self.attributes
                 .iter()
-                .flat_map(|a| a.to_dot_statements(self.name.clone())),
+                .flat_map(|a| a.to_dot_statements(self.name.clone(), false)),
         );
 
         // Draw attribute lines
@@ -360,7 +586,17 @@ impl ToDot for Relation {
         for member in self.members.iter() {
             let (amount, new_next_multiple_amount) =
                 member.cardinality.get_amount(next_multiple_amount);
+            let amount = match notation {
+                Notation::Cardinality => amount,
+                Notation::MinMax => {
+                    let (min, max) = member.bounds();
+                    let max = max.map_or(next_multiple_amount.to_string(), |m| m.to_string());
+                    format!("({},{})", min, max)
+                }
+            };
             next_multiple_amount = new_next_multiple_amount;
+            let is_double = notation == Notation::Cardinality
+                && member.optionality == RelationOptionality::Required;
             statements.push(dot::Statement::Edge(dot::EdgeStatement {
                 left: self.name.clone().into(),
                 right: dot::EdgeRHS {
@@ -372,15 +608,21 @@ impl ToDot for Relation {
                     content: dot::AList(vec![
                         dot::AListItem {
                             key: "color".into(),
-                            value: match member.optionality {
-                                RelationOptionality::Optional => "black",
-                                RelationOptionality::Required => "\"black:invis:invis:black\"",
+                            value: if is_double {
+                                "\"black:invis:invis:black\""
+                            } else {
+                                "black"
                             }
                             .into(),
                         },
                         dot::AListItem {
                             key: "label".into(),
-                            value: format!("<<font color=\"blue\">{}</font>>", amount),
+                            value: match &member.role {
+                                Some(role) => {
+                                    format!("<<font color=\"blue\">{}</font> {}>", amount, role)
+                                }
+                                None => format!("<<font color=\"blue\">{}</font>>", amount),
+                            },
                         },
                         dot::AListItem {
                             key: "len".into(),
@@ -397,37 +639,94 @@ impl ToDot for Relation {
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ERDError {
-    DuplicateIdent(Ident),
-    DuplicateAttributeInEntity(Ident, Ident), // Attribute, Entity
-    DuplicateAttributeInRelation(Ident, Ident), // Attribute, Relation
-    UnknownEntityInRelation(Ident, Ident),    // Entity, Relation
+    DuplicateIdent(Ident, Span, Span), // Ident, First definition, Duplicate
+    DuplicateAttributeInEntity(Ident, Ident, Span), // Attribute, Entity
+    DuplicateAttributeInRelation(Ident, Ident, Span), // Attribute, Relation
+    UnknownEntityInRelation(Ident, Ident, Option<Ident>, Span), // Entity, Relation, Suggestion
+    MissingRoleInRelation(Ident, Ident, Span), // Entity, Relation
+    DuplicateRoleInRelation(Ident, Ident, Span), // Role, Relation
+    InvalidIdentifyingRelation(Ident, Span), // Relation
+    UnidentifiedWeakEntity(Ident, Span), // Entity
+    CyclicWeakEntity(Ident, Span),     // Entity
 }
-impl std::fmt::Display for ERDError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+
+impl ERDError {
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::DuplicateIdent(_, _, s)
+            | Self::DuplicateAttributeInEntity(_, _, s)
+            | Self::DuplicateAttributeInRelation(_, _, s)
+            | Self::UnknownEntityInRelation(_, _, _, s)
+            | Self::MissingRoleInRelation(_, _, s)
+            | Self::DuplicateRoleInRelation(_, _, s)
+            | Self::InvalidIdentifyingRelation(_, s)
+            | Self::UnidentifiedWeakEntity(_, s)
+            | Self::CyclicWeakEntity(_, s) => s,
+        }
+    }
+
+    /// The error message without its location
+    pub fn message(&self) -> String {
         match self {
-            Self::DuplicateIdent(i) => write!(f, "Name {} is used multiple times.", i),
-            Self::DuplicateAttributeInEntity(i, e) => {
-                write!(f, "Multiple attributes named {} in entity {}.", i, e)
+            Self::DuplicateIdent(i, _, _) => format!("Name {} is used multiple times.", i),
+            Self::DuplicateAttributeInEntity(i, e, _) => {
+                format!("Multiple attributes named {} in entity {}.", i, e)
+            }
+            Self::DuplicateAttributeInRelation(i, e, _) => {
+                format!("Multiple attributes named {} in relation {}.", i, e)
+            }
+            Self::UnknownEntityInRelation(e, r, _, _) => {
+                format!("Unknown entity {} in relation {}.", e, r)
             }
-            Self::DuplicateAttributeInRelation(i, e) => {
-                write!(f, "Multiple attributes named {} in relation {}.", i, e)
+            Self::MissingRoleInRelation(e, r, _) => {
+                format!(
+                    "Entity {} occurs multiple times in relation {} without a role.",
+                    e, r
+                )
             }
-            Self::UnknownEntityInRelation(e, r) => {
-                write!(f, "Unknown entity {} in relation {}.", e, r)
+            Self::DuplicateRoleInRelation(role, r, _) => {
+                format!("Multiple members with role {} in relation {}.", role, r)
+            }
+            Self::InvalidIdentifyingRelation(r, _) => {
+                format!(
+                    "Identifying relation {} doesn't relate a weak entity to one required owner.",
+                    r
+                )
+            }
+            Self::UnidentifiedWeakEntity(e, _) => {
+                format!("Weak entity {} needs exactly one identifying relation.", e)
+            }
+            Self::CyclicWeakEntity(e, _) => {
+                format!(
+                    "Weak entity {} is identified by itself through its owners.",
+                    e
+                )
             }
         }
     }
 }
 
+impl std::fmt::Display for ERDError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.span().is_empty() {
+            write!(f, "{}", self.message())
+        } else {
+            write!(f, "{}: {}", self.span(), self.message())
+        }
+    }
+}
+
 impl std::convert::TryFrom<Vec<Expr>> for ERD {
     type Error = Vec<ERDError>;
     fn try_from(v: Vec<Expr>) -> Result<ERD, Vec<ERDError>> {
         let entities = v
             .iter()
             .filter_map(|expr| match expr {
-                Expr::Entity(name, attributes) => Some(Entity {
+                Expr::Entity(name, attributes, weak, span) => Some(Entity {
                     name: name.clone(),
                     attributes: attributes.clone(),
+                    weak: *weak,
+                    span: span.clone(),
                 }),
                 _ => None,
             })
@@ -435,12 +734,16 @@ impl std::convert::TryFrom<Vec<Expr>> for ERD {
         let relations = v
             .iter()
             .filter_map(|expr| match expr {
-                Expr::Relation(name, label, members, attributes) => Some(Relation {
-                    name: name.clone(),
-                    label: label.clone(),
-                    members: members.clone(),
-                    attributes: attributes.clone(), // TODO
-                }),
+                Expr::Relation(name, label, members, attributes, identifying, span) => {
+                    Some(Relation {
+                        name: name.clone(),
+                        label: label.clone(),
+                        members: members.clone(),
+                        attributes: attributes.clone(), // TODO
+                        identifying: *identifying,
+                        span: span.clone(),
+                    })
+                }
                 _ => None,
             })
             .collect();
@@ -467,13 +770,41 @@ impl<T: ToDot> ToDot for Vec<T> {
     }
 }
 
+/// How the cardinalities of the relation members are drawn
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Notation {
+    /// `1`, `n`, `m`, ... with a double line for required members
+    #[default]
+    Cardinality,
+    /// `(min,max)` intervals, like `(0,1)` and `(2,11)`
+    MinMax,
+}
+
+impl std::str::FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cardinality" => Ok(Self::Cardinality),
+            "min_max" => Ok(Self::MinMax),
+            _ => Err(format!("Unknown notation {}.", s)),
+        }
+    }
+}
+
 impl ToDot for ERD {
     fn to_dot_statements(&self) -> Vec<dot::Statement> {
-        let mut statements = Vec::new();
+        self.to_dot_statements_with(Notation::default())
+    }
+}
 
-        statements.push(dot::Statement::ID("layout".into(), "neato".into()));
-        statements.push(dot::Statement::ID("forcelabels".into(), "true".into()));
-        statements.push(dot::Statement::ID("overlap".into(), "scale".into()));
+impl ERD {
+    fn to_dot_statements_with(&self, notation: Notation) -> Vec<dot::Statement> {
+        let mut statements = vec![
+            dot::Statement::ID("layout".into(), "neato".into()),
+            dot::Statement::ID("forcelabels".into(), "true".into()),
+            dot::Statement::ID("overlap".into(), "scale".into()),
+        ];
 
         statements.push(dot::Statement::Attribute(dot::AttributeStatement {
             r#type: dot::AttributeStatementType::Graph,
@@ -497,19 +828,137 @@ impl ToDot for ERD {
         }));
 
         statements.extend(self.entities.to_dot_statements());
-        statements.extend(self.relations.to_dot_statements());
+        statements.extend(
+            self.relations
+                .iter()
+                .flat_map(|r| r.to_dot_statements_with(notation)),
+        );
 
         statements
     }
-}
 
-impl ERD {
     pub fn to_dot(&self) -> dot::Graph {
+        self.to_dot_with(Notation::default())
+    }
+
+    pub fn to_dot_with(&self, notation: Notation) -> dot::Graph {
         dot::Graph {
             strict: false,
             r#type: dot::GraphType::Normal,
             id: None, // TODO
-            statements: self.to_dot_statements(),
+            statements: self.to_dot_statements_with(notation),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn errors_report_their_location() {
+        let errors = match ERD::from_script("entity Student\n\nentity Course\n\nentity Student") {
+            Err(ERDFromScriptError::ERDError(errors)) => errors,
+            other => panic!("Expected ERD errors, got {:?}", other),
+        };
+        assert_eq!(errors.len(), 1);
+        let span = errors[0].span();
+        assert_eq!((span.line, span.column), (5, 8));
+        assert_eq!(
+            errors[0].to_string(),
+            "5:8: Name Student is used multiple times."
+        );
+        // Generated nodes, e.g. of reverse engineering, have no location
+        assert_eq!(
+            ERDError::DuplicateIdent("A".to_string().into(), Span::default(), Span::default())
+                .to_string(),
+            "Name A is used multiple times."
+        );
+    }
+
+    #[test]
+    fn report_all_parsing_errors() {
+        let errors = match ERD::from_script(
+            "entity A\n  atribute x\n\nrelation R\n  exactly(0) required A\n  one optional A",
+        ) {
+            Err(ERDFromScriptError::ParsingError(errors)) => errors,
+            other => panic!("Expected parsing errors, got {:?}", other),
+        };
+        assert!(matches!(
+            &errors[..],
+            [
+                crate::parser::ConsumeError::ERDParseError(_),
+                crate::parser::ConsumeError::ConversionError(_)
+            ]
+        ));
+    }
+
+    #[test]
+    fn draw_min_max_notation() {
+        let erd = ERD::from_script(
+            "entity Team\n\nentity Player\n\nrelation Plays\n  between(2, 11) required Player\n  one optional Team",
+        )
+        .unwrap();
+        let dot = erd.to_dot_with(Notation::MinMax).to_string();
+        assert!(dot.contains("<<font color=\"blue\">(2,11)</font>>"));
+        assert!(dot.contains("<<font color=\"blue\">(0,1)</font>>"));
+        assert!(!dot.contains("invis"));
+        assert!(erd
+            .to_dot()
+            .to_string()
+            .contains("<<font color=\"blue\">2..11</font>>"));
+    }
+
+    #[test]
+    fn repeated_entities_need_roles() {
+        let errors = match ERD::from_script(
+            "entity Person\n\nrelation Mother\n  one optional Person as mother\n  multiple optional Person",
+        ) {
+            Err(ERDFromScriptError::ERDError(errors)) => errors,
+            other => panic!("Expected ERD errors, got {:?}", other),
+        };
+        assert!(matches!(&errors[..], [ERDError::MissingRoleInRelation(..)]));
+
+        let errors = match ERD::from_script(
+            "entity Person\n\nrelation Mother\n  one optional Person as mother\n  multiple optional Person as mother",
+        ) {
+            Err(ERDFromScriptError::ERDError(errors)) => errors,
+            other => panic!("Expected ERD errors, got {:?}", other),
+        };
+        assert!(matches!(
+            &errors[..],
+            [ERDError::DuplicateRoleInRelation(..)]
+        ));
+    }
+
+    #[test]
+    fn weak_entities_need_an_owner() {
+        let errors = match ERD::from_script(
+            "entity Building\n\nweak entity Room\n\nidentifying relation Contains\n  one optional Building\n  multiple optional Room",
+        ) {
+            Err(ERDFromScriptError::ERDError(errors)) => errors,
+            other => panic!("Expected ERD errors, got {:?}", other),
+        };
+        assert!(matches!(
+            &errors[..],
+            [
+                ERDError::InvalidIdentifyingRelation(..),
+                ERDError::UnidentifiedWeakEntity(..)
+            ]
+        ));
+
+        let errors = match ERD::from_script(
+            "weak entity A\n\nweak entity B\n\nidentifying relation AB\n  one required A\n  multiple optional B\n\nidentifying relation BA\n  one required B\n  multiple optional A",
+        ) {
+            Err(ERDFromScriptError::ERDError(errors)) => errors,
+            other => panic!("Expected ERD errors, got {:?}", other),
+        };
+        assert!(matches!(
+            &errors[..],
+            [
+                ERDError::CyclicWeakEntity(..),
+                ERDError::CyclicWeakEntity(..)
+            ]
+        ));
+    }
+}
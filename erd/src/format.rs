@@ -0,0 +1,222 @@
+use crate::ast::Expr;
+use std::collections::HashMap;
+
+/// A `//` comment, `line` is 1-based
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Comment {
+    line: usize,
+    text: String,
+}
+
+/// Finds the comments of the source. `//` is no comment within quotes, like in import paths
+/// and `default` literals, or within `check` expressions.
+fn comments(source: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let mut double_quoted = false;
+        let mut single_quoted = false;
+        // A quote in a label or enum is no string, they end at the first `)`. Checks nest.
+        let mut in_group = false;
+        let mut check_depth = 0;
+        let mut previous = ' ';
+        for (idx, c) in line.char_indices() {
+            let in_check = check_depth > 0;
+            match c {
+                '"' if !single_quoted && !in_group && !in_check => double_quoted = !double_quoted,
+                // A `''` escape closes and reopens the string
+                '\'' if !double_quoted && !in_group => single_quoted = !single_quoted,
+                '(' if single_quoted || double_quoted || in_group => (),
+                '(' if in_check => check_depth += 1,
+                '(' if line[..idx].trim_end().ends_with("check") => check_depth = 1,
+                '(' => in_group = true,
+                ')' if single_quoted || double_quoted => (),
+                ')' if in_check => check_depth -= 1,
+                ')' => in_group = false,
+                '/' if previous == '/'
+                    && !double_quoted
+                    && !single_quoted
+                    && !in_group
+                    && !in_check =>
+                {
+                    comments.push(Comment {
+                        line: i + 1,
+                        text: line[idx - 1..].trim_end().to_string(),
+                    });
+                    break;
+                }
+                _ => (),
+            }
+            previous = c;
+        }
+    }
+    comments
+}
+
+/// The formatted lines of an expression together with the line they come from.
+/// Members of a relation are written before its attributes.
+fn lines(expr: &Expr) -> Vec<(usize, String)> {
+    let header = expr.span().line;
+    match expr {
+        Expr::Entity(name, attributes, weak, _) => {
+            let weak = if *weak { "weak " } else { "" };
+            std::iter::once((header, format!("{}entity {}", weak, name)))
+                .chain(attributes.iter().map(|a| (a.span.line, format!("  {}", a))))
+                .collect()
+        }
+        Expr::Relation(name, label, members, attributes, identifying, _) => {
+            let label = label
+                .as_ref()
+                .map(|l| format!("({})", l))
+                .unwrap_or_default();
+            let identifying = if *identifying { "identifying " } else { "" };
+            std::iter::once((header, format!("{}relation {}{}", identifying, name, label)))
+                .chain(members.iter().map(|m| (m.span.line, format!("  {}", m))))
+                .chain(attributes.iter().map(|a| (a.span.line, format!("  {}", a))))
+                .collect()
+        }
+        Expr::EntityTable(name, entity, foreign_keys, _) => {
+            std::iter::once((header, format!("table {} from entity {}", name, entity)))
+                .chain(foreign_keys.iter().map(|f| (f.span.line, f.to_string())))
+                .collect()
+        }
+        Expr::RelationTable(_, _, _) | Expr::Import(_, _) => vec![(header, expr.to_string())],
+    }
+}
+
+enum Item<'a> {
+    Expr(&'a Expr, Vec<(usize, String)>),
+    Comment(Comment),
+}
+
+impl Item<'_> {
+    fn first_line(&self) -> usize {
+        match self {
+            Self::Expr(_, lines) => lines.iter().map(|(l, _)| *l).min().unwrap_or(0),
+            Self::Comment(c) => c.line,
+        }
+    }
+
+    fn last_line(&self) -> usize {
+        match self {
+            Self::Expr(_, lines) => lines.iter().map(|(l, _)| *l).max().unwrap_or(0),
+            Self::Comment(c) => c.line,
+        }
+    }
+}
+
+/// Writes `source` with consistent indentation and spacing, `expressions` are the parsed
+/// expressions of `source`.
+/// Blocks are separated by a blank line, consecutive imports and comments that are directly
+/// above a block are kept together with it.
+pub fn format_source(source: &str, expressions: &[Expr]) -> String {
+    let source_lines: Vec<&str> = source.lines().collect();
+    let blank_between = |first: usize, last: usize| {
+        (first + 1..last).any(|l| {
+            source_lines
+                .get(l - 1)
+                .map(|s| s.trim().is_empty())
+                .unwrap_or(true)
+        })
+    };
+
+    let mut trailing: HashMap<usize, String> = HashMap::new();
+    let mut items: Vec<Item> = expressions
+        .iter()
+        .map(|e| Item::Expr(e, lines(e)))
+        .collect();
+    let element_lines: Vec<usize> = items
+        .iter()
+        .flat_map(|i| match i {
+            Item::Expr(_, lines) => lines.iter().map(|(l, _)| *l).collect(),
+            Item::Comment(_) => Vec::new(),
+        })
+        .collect();
+    for comment in comments(source) {
+        if element_lines.contains(&comment.line) {
+            trailing.insert(comment.line, comment.text);
+        } else {
+            items.push(Item::Comment(comment));
+        }
+    }
+    items.sort_by_key(|i| i.first_line());
+
+    let mut s = String::new();
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            let previous = &items[i - 1];
+            let adjacent = !blank_between(previous.last_line(), item.first_line());
+            // Comments that are directly above an expression belong to that expression
+            let attached_to_next = || {
+                let mut k = i;
+                while let Item::Comment(_) = items[k] {
+                    match items.get(k + 1) {
+                        Some(next) if !blank_between(items[k].last_line(), next.first_line()) => {
+                            k += 1
+                        }
+                        _ => return false,
+                    }
+                }
+                true
+            };
+            let together = match (previous, item) {
+                (Item::Expr(Expr::Import(_, _), _), Item::Expr(Expr::Import(_, _), _)) => adjacent,
+                (Item::Expr(_, _), Item::Expr(_, _)) => false,
+                (Item::Comment(_), _) => adjacent,
+                (Item::Expr(_, _), Item::Comment(_)) => adjacent && !attached_to_next(),
+            };
+            s.push_str(if together { "\n" } else { "\n\n" });
+        }
+        match item {
+            Item::Expr(_, lines) => {
+                for (j, (line, text)) in lines.iter().enumerate() {
+                    if j > 0 {
+                        s.push('\n');
+                    }
+                    s.push_str(text);
+                    if let Some(comment) = trailing.get(line) {
+                        s.push(' ');
+                        s.push_str(comment);
+                    }
+                }
+            }
+            Item::Comment(comment) => s.push_str(&comment.text),
+        }
+    }
+    s.push('\n');
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format(script: &str) -> String {
+        let expressions =
+            crate::parser::consume_expressions(crate::parser::parse_as_erd(script).unwrap())
+                .unwrap();
+        format_source(script, &expressions)
+    }
+
+    #[test]
+    fn format_is_stable() {
+        let script = "import \"a.erd\"\nimport \"b.erd\"\n\n\n\nentity   Person\n  id   id type autoincrement\n      attribute name type varchar(100)\n\nrelation Owns(Is owner)\n  attribute since type decimal(10,2)\n  exactly(2)  required Person\n  multiple optional Car\n\ntable person from entity Person\n   foreign key a,b for Owns\n\ntable owns from relation Owns";
+        let expected = "import \"a.erd\"\nimport \"b.erd\"\n\nentity Person\n  id id type autoincrement\n  attribute name type varchar(100)\n\nrelation Owns(Is owner)\n  exactly(2) required Person\n  multiple optional Car\n  attribute since type decimal(10, 2)\n\ntable person from entity Person\nforeign key a, b for Owns\n\ntable owns from relation Owns\n";
+        assert_eq!(format(script), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn keep_comments() {
+        let script = "// Persons\n\n// The person\nentity Person // a human\n  id id   // unique\n\n\n\n// A car\nentity Car\n\nimport \"http://a.erd\" // remote\n// The end";
+        let expected = "// Persons\n\n// The person\nentity Person // a human\n  id id // unique\n\n// A car\nentity Car\n\nimport \"http://a.erd\" // remote\n// The end\n";
+        assert_eq!(format(script), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn slashes_in_literals_are_no_comments() {
+        let script = "entity Site // a website\n  id url type text default 'http://x' // the address\n  attribute path type text default 'it''s //x'\n  attribute c type text check(c <> '//' AND (c <> 'a)b' OR c // 2 > 0))\n\nrelation Links(Site's links) // to others\n  one optional Site as a\n  multiple optional Site as b\n";
+        assert_eq!(format(script), script);
+        assert_eq!(format(&format(script)), script);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::dot::{Graph, Statement};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The output formats of Graphviz that can be requested
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Svg,
+    Png,
+    Pdf,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            "pdf" => Ok(Self::Pdf),
+            _ => Err(format!("Unknown output format {}.", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Svg => write!(f, "svg"),
+            Self::Png => write!(f, "png"),
+            Self::Pdf => write!(f, "pdf"),
+        }
+    }
+}
+
+/// The layout engines of Graphviz
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Engine {
+    Dot,
+    Neato,
+    Fdp,
+    Sfdp,
+    Circo,
+    Twopi,
+}
+
+impl std::str::FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "neato" => Ok(Self::Neato),
+            "fdp" => Ok(Self::Fdp),
+            "sfdp" => Ok(Self::Sfdp),
+            "circo" => Ok(Self::Circo),
+            "twopi" => Ok(Self::Twopi),
+            _ => Err(format!("Unknown layout engine {}.", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dot => write!(f, "dot"),
+            Self::Neato => write!(f, "neato"),
+            Self::Fdp => write!(f, "fdp"),
+            Self::Sfdp => write!(f, "sfdp"),
+            Self::Circo => write!(f, "circo"),
+            Self::Twopi => write!(f, "twopi"),
+        }
+    }
+}
+
+/// The output of Graphviz together with the warnings it printed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendered {
+    pub output: Vec<u8>,
+    pub warnings: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphvizError {
+    NotInstalled,
+    Io(String),
+    Failed(Option<i32>, String), // Exit code, stderr
+}
+
+impl std::fmt::Display for GraphvizError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInstalled => write!(
+                f,
+                "Graphviz is not installed, the `dot` command could not be found. See https://graphviz.org/download/."
+            ),
+            Self::Io(e) => write!(f, "Could not run Graphviz: {}.", e),
+            Self::Failed(Some(code), stderr) => {
+                write!(f, "Graphviz failed with exit code {}: {}", code, stderr.trim_end())
+            }
+            Self::Failed(None, stderr) => write!(f, "Graphviz failed: {}", stderr.trim_end()),
+        }
+    }
+}
+
+/// Renders the graph by streaming it to the `dot` command.
+/// `engine` replaces the layout that is set in the graph itself.
+pub fn render(
+    graph: &Graph,
+    format: Format,
+    engine: Option<Engine>,
+) -> Result<Rendered, GraphvizError> {
+    render_with("dot", graph, format, engine)
+}
+
+/// The graph with its layout replaced by `engine`
+fn with_engine(graph: &Graph, engine: Option<Engine>) -> Graph {
+    let mut graph = graph.clone();
+    if let Some(engine) = engine {
+        graph
+            .statements
+            .retain(|s| !matches!(s, Statement::ID(key, _) if key == "layout"));
+        graph
+            .statements
+            .insert(0, Statement::ID("layout".into(), engine.to_string()));
+    }
+    graph
+}
+
+/// The command that reads a graph from stdin and writes it in `format` to stdout
+fn command(program: &str, format: Format) -> Command {
+    let mut command = Command::new(program);
+    command
+        .arg(format!("-T{}", format))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    command
+}
+
+fn render_with(
+    program: &str,
+    graph: &Graph,
+    format: Format,
+    engine: Option<Engine>,
+) -> Result<Rendered, GraphvizError> {
+    let graph = with_engine(graph, engine);
+    let mut child = command(program, format)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => GraphvizError::NotInstalled,
+            _ => GraphvizError::Io(e.to_string()),
+        })?;
+    // Dot reads the whole graph before writing, so stdin can be written before reading stdout
+    let written = child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(graph.to_string().as_bytes());
+    let output = child
+        .wait_with_output()
+        .map_err(|e| GraphvizError::Io(e.to_string()))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        return Err(GraphvizError::Failed(output.status.code(), stderr));
+    }
+    written.map_err(|e| GraphvizError::Io(e.to_string()))?;
+    Ok(Rendered {
+        output: output.stdout,
+        warnings: stderr,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::erd::ERD;
+
+    fn graph() -> Graph {
+        ERD::from_script("entity Person\n  id id").unwrap().to_dot()
+    }
+
+    #[test]
+    fn missing_dot_is_not_installed() {
+        assert_eq!(
+            render_with("erd-script-missing-dot", &graph(), Format::Svg, None),
+            Err(GraphvizError::NotInstalled)
+        );
+    }
+
+    #[test]
+    fn pass_the_format_to_dot() {
+        let command = command("dot", Format::Png);
+        assert_eq!(command.get_program(), "dot");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["-Tpng"]);
+        assert_eq!("pdf".parse::<Format>(), Ok(Format::Pdf));
+        assert!("jpg".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn engine_replaces_the_layout() {
+        let graph = graph();
+        let layouts = |g: &Graph| -> Vec<String> {
+            g.statements
+                .iter()
+                .filter_map(|s| match s {
+                    Statement::ID(key, value) if key == "layout" => Some(value.to_string()),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(layouts(&with_engine(&graph, None)), vec!["neato"]);
+        let circo = with_engine(&graph, Some(Engine::Circo));
+        assert_eq!(layouts(&circo), vec!["circo"]);
+        assert!(circo.to_string().contains("layout=circo"));
+    }
+}
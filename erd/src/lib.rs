@@ -1,9 +1,15 @@
 pub mod ast;
+pub mod diagnostic;
 pub mod dot;
 pub mod erd;
+pub mod format;
+pub mod graphviz;
 pub mod parser;
 pub mod physical;
+pub mod project;
+pub mod reverse;
 pub mod sql;
+pub mod svg;
 
 extern crate pest;
 #[macro_use]
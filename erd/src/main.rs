@@ -1,75 +1,526 @@
-use clap::Parser;
-use erd_script::parser::ConsumeError;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use erd_script::ast::Expr;
+use erd_script::diagnostic::{Diagnostic, ToDiagnostic};
+use erd_script::erd::{Notation, ERD};
+use erd_script::graphviz;
+use erd_script::physical::{PhysicalDescription, PhysicalERDError, PhysicalError};
+use erd_script::project::{Project, SourceFile};
+use erd_script::reverse::{ReverseEngineered, Schema};
 use erd_script::sql::SQL;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::io::{IsTerminal, Read, Write};
+use std::process::ExitCode;
 
-/// Compile an erd-script file to an svg
+/// Compile erd-script files to diagrams and sql
 #[derive(Parser)]
 #[command(version = "1.0", author = "Jesse Hoobergs")]
 struct Opts {
-    /// The path to an erd-script file-Some input. Because this isn't an Option<T> it's required to be used
-    file_path: String,
-    /// The path where the output svg should be written
-    output_path: String,
+    /// When to use colors in the error output
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+    color: ColorChoice,
+    #[command(subcommand)]
+    command: Command,
 }
 
-fn parse_file(path: &std::path::Path) -> Result<Vec<erd_script::ast::Expr>, ConsumeError> {
-    let content = std::fs::read_to_string(path).expect("Valid file");
-    let pairs = erd_script::parser::parse_as_erd(&content)
-        .map_err(|e| erd_script::parser::ConsumeError::ERDParseError(vec![e]))?;
-    let asts = erd_script::parser::consume_expressions(pairs)?;
-    Ok(asts)
+#[derive(Subcommand)]
+enum Command {
+    /// Check an erd-script file and its physical mappings for errors
+    Check {
+        #[command(flatten)]
+        input: Input,
+        #[command(flatten)]
+        mappings: Mappings,
+    },
+    /// Convert an erd-script file to a Graphviz dot graph
+    Dot {
+        #[command(flatten)]
+        input: Input,
+        /// How cardinalities are drawn: cardinality (1, n, m, ...) or min_max ((0,1), (2,11), ...)
+        #[arg(long, default_value = "cardinality")]
+        notation: Notation,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Draw an erd-script file with Graphviz
+    #[command(alias = "svg")]
+    Render {
+        #[command(flatten)]
+        input: Input,
+        /// Draw with Graphviz or with the built-in renderer, which only supports svg
+        #[arg(long, value_enum, default_value_t = Backend::Graphviz)]
+        backend: Backend,
+        /// The output format: svg, png or pdf
+        #[arg(long, default_value = "svg")]
+        format: graphviz::Format,
+        /// The layout engine of Graphviz: dot, neato, fdp, sfdp, circo or twopi.
+        /// Without it, neato is used
+        #[arg(long)]
+        engine: Option<graphviz::Engine>,
+        /// How cardinalities are drawn: cardinality (1, n, m, ...) or min_max ((0,1), (2,11), ...)
+        #[arg(long, default_value = "cardinality")]
+        notation: Notation,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Generate the sql to create the tables of the physical mappings
+    Sql {
+        #[command(flatten)]
+        input: Input,
+        #[command(flatten)]
+        mappings: Mappings,
+        /// The dbms to generate sql for: ms_access, libre_office_base, mysql, postgresql, mssql, sqlite, oracle or db2
+        #[arg(long, default_value = "libre_office_base")]
+        dialect: SQL,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Format an erd-script file
+    Fmt {
+        #[command(flatten)]
+        input: Input,
+        /// Only check if the file is formatted, fails when it isn't
+        #[arg(long)]
+        check: bool,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Export the ERD to be used by other tools
+    Export {
+        #[command(flatten)]
+        input: Input,
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Reverse engineer the entities, relations and tables of a sql dump to erd-script
+    Import {
+        /// The path to a file with CREATE TABLE statements, stdin is read when it is missing
+        /// or `-`
+        input: Option<String>,
+        /// Read the tables of a SQLite database file instead
+        #[arg(long, conflicts_with = "input")]
+        sqlite: Option<String>,
+        #[command(flatten)]
+        output: Output,
+    },
 }
 
-fn compile_dot(dot: &erd_script::dot::Graph) -> std::io::Result<std::process::Output> {
-    let dot = dot.to_string();
-    let dot_file = "tmp.dot";
-    std::fs::write(dot_file, dot).expect("failed writing");
-    std::process::Command::new("dot")
-        .arg("-Tsvg")
-        .arg(dot_file)
-        .output()
+#[derive(Args)]
+struct Input {
+    /// The path to an erd-script file, stdin is read when it is missing or `-`
+    input: Option<String>,
 }
 
-fn main() {
+impl Input {
+    fn path(&self) -> Option<&str> {
+        self.input.as_deref().filter(|p| *p != "-")
+    }
+
+    fn name(&self) -> &str {
+        self.path().unwrap_or("<stdin>")
+    }
+
+    fn read(&self) -> Result<String, Failure> {
+        let mut content = String::new();
+        match self.path() {
+            Some(path) => {
+                std::fs::File::open(path).and_then(|mut f| f.read_to_string(&mut content))
+            }
+            None => std::io::stdin().read_to_string(&mut content),
+        }
+        .map_err(|e| Failure::Message(format!("could not read {}: {}", self.name(), e)))?;
+        Ok(content)
+    }
+}
+
+#[derive(Args)]
+struct Output {
+    /// The path where the output should be written, stdout is used when it is missing or `-`
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+impl Output {
+    fn write(&self, content: &[u8]) -> Result<(), Failure> {
+        match self.output.as_deref().filter(|p| *p != "-") {
+            Some(path) => std::fs::write(path, content)
+                .map_err(|e| Failure::Message(format!("could not write {}: {}", path, e))),
+            None => std::io::stdout()
+                .write_all(content)
+                .map_err(|e| Failure::Message(format!("could not write to stdout: {}", e))),
+        }
+    }
+}
+
+#[derive(Args)]
+struct Mappings {
+    /// The path to a physical mapping of the ERD, can be used multiple times.
+    /// Without it, the tables in the erd-script file are used
+    #[arg(short, long = "physical")]
+    physical_paths: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Backend {
+    Graphviz,
+    Native,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(&self) -> bool {
+        match self {
+            Self::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+enum Failure {
+    Diagnostics(Vec<Diagnostic>),
+    Message(String),
+}
+
+fn report_error(message: &str, color: bool) {
+    if color {
+        eprintln!("\x1b[1;31merror\x1b[0m\x1b[1m: {}\x1b[0m", message);
+    } else {
+        eprintln!("error: {}", message);
+    }
+}
+
+fn report_warning(message: &str, color: bool) {
+    if color {
+        eprintln!("\x1b[1;33mwarning\x1b[0m\x1b[1m: {}\x1b[0m", message);
+    } else {
+        eprintln!("warning: {}", message);
+    }
+}
+
+fn report_diagnostics(mut diagnostics: Vec<Diagnostic>, sources: &[SourceFile], color: bool) {
+    diagnostics.sort_by_key(|d| d.span.as_ref().map(|s| (s.file.clone(), s.start)));
+    diagnostics.dedup();
+    for diagnostic in diagnostics.iter() {
+        let file = diagnostic.span.as_ref().and_then(|s| s.file.as_deref());
+        let source = sources
+            .iter()
+            .find(|s| Some(&s.path[..]) == file)
+            .map(|s| &s.content[..])
+            .unwrap_or_default();
+        eprintln!("{}", diagnostic.render(source, color));
+    }
+    let message = match diagnostics.len() {
+        1 => "aborting due to 1 previous error".to_string(),
+        n => format!("aborting due to {} previous errors", n),
+    };
+    report_error(&message, color);
+}
+
+/// Errors that point into the ERD mention the mapping they come from
+fn physical_diagnostics(errors: Vec<PhysicalError>, mapping: Option<&str>) -> Vec<Diagnostic> {
+    errors
+        .iter()
+        .map(|e| {
+            let mut diagnostic = e.to_diagnostic();
+            if let Some(mapping) = mapping {
+                if e.span().file.as_deref() != Some(mapping) {
+                    diagnostic.message = format!(
+                        "{} (in mapping {}).",
+                        diagnostic.message.trim_end_matches('.'),
+                        mapping
+                    );
+                }
+            }
+            diagnostic
+        })
+        .collect()
+}
+
+fn load_project(input: &Input, sources: &mut Vec<SourceFile>) -> Result<Project, Failure> {
+    let project = match input.path() {
+        Some(path) => Project::load(std::path::Path::new(path)),
+        None => Project::from_source(input.name(), input.read()?),
+    };
+    sources.extend(project.files().iter().cloned());
+    Ok(project)
+}
+
+fn to_erd(project: &Project) -> Result<ERD, Failure> {
+    // Syntax errors are reported together with the errors of the well-formed expressions
+    let mut diagnostics = project.diagnostics();
+    match ERD::try_from(project.expressions()) {
+        Ok(erd) if diagnostics.is_empty() => Ok(erd),
+        Ok(_) => Err(Failure::Diagnostics(diagnostics)),
+        Err(errors) => {
+            diagnostics.extend(errors.iter().map(|e| e.to_diagnostic()));
+            Err(Failure::Diagnostics(diagnostics))
+        }
+    }
+}
+
+/// The physical descriptions of all mappings together with the path of the mapping
+fn to_physicals(
+    project: &Project,
+    mappings: &Mappings,
+    sources: &mut Vec<SourceFile>,
+) -> Result<Vec<(Option<String>, PhysicalDescription)>, Failure> {
+    if mappings.physical_paths.is_empty() {
+        let mut diagnostics = project.diagnostics();
+        return match project.expressions().try_into() {
+            Ok(physical) if diagnostics.is_empty() => Ok(vec![(None, physical)]),
+            Ok(_) => Err(Failure::Diagnostics(diagnostics)),
+            Err(PhysicalERDError::ERD(errors)) => {
+                diagnostics.extend(errors.iter().map(|e| e.to_diagnostic()));
+                Err(Failure::Diagnostics(diagnostics))
+            }
+            Err(PhysicalERDError::Physical(errors)) => {
+                diagnostics.extend(physical_diagnostics(errors, None));
+                Err(Failure::Diagnostics(diagnostics))
+            }
+        };
+    }
+
+    let erd = to_erd(project)?;
+    let mut diagnostics = Vec::new();
+    let mut physicals = Vec::new();
+    for path in mappings.physical_paths.iter() {
+        let mapping = Project::load(std::path::Path::new(path));
+        sources.extend(mapping.files().iter().cloned());
+        diagnostics.extend(mapping.diagnostics());
+        match (erd.clone(), mapping.expressions()).try_into() {
+            Ok(physical) => physicals.push((Some(path.clone()), physical)),
+            Err(errors) => diagnostics.extend(physical_diagnostics(errors, Some(path))),
+        }
+    }
+    if diagnostics.is_empty() {
+        Ok(physicals)
+    } else {
+        Err(Failure::Diagnostics(diagnostics))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn read_sqlite(path: &str) -> Result<Schema, Failure> {
+    Schema::from_sqlite(std::path::Path::new(path))
+        .map_err(|e| Failure::Message(format!("could not read {}: {}", path, e)))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn read_sqlite(_path: &str) -> Result<Schema, Failure> {
+    Err(Failure::Message(
+        "erd_script is built without support for SQLite, build it with `--features sqlite`"
+            .to_string(),
+    ))
+}
+
+fn run(command: Command, sources: &mut Vec<SourceFile>, color: bool) -> Result<(), Failure> {
+    match command {
+        Command::Check { input, mappings } => {
+            let project = load_project(&input, sources)?;
+            let has_tables = project.expressions().iter().any(|e| {
+                matches!(
+                    e,
+                    Expr::EntityTable(_, _, _, _) | Expr::RelationTable(_, _, _)
+                )
+            });
+            if mappings.physical_paths.is_empty() && !has_tables {
+                to_erd(&project).map(|_| ())
+            } else {
+                to_physicals(&project, &mappings, sources).map(|_| ())
+            }
+        }
+        Command::Dot {
+            input,
+            notation,
+            output,
+        } => {
+            let erd = to_erd(&load_project(&input, sources)?)?;
+            output.write(erd.to_dot_with(notation).to_string().as_bytes())
+        }
+        Command::Render {
+            input,
+            backend: Backend::Native,
+            format,
+            engine,
+            notation,
+            output,
+        } => {
+            if format != graphviz::Format::Svg || engine.is_some() {
+                return Err(Failure::Message(
+                    "the native renderer only draws svg, without choosing a layout engine"
+                        .to_string(),
+                ));
+            }
+            let erd = to_erd(&load_project(&input, sources)?)?;
+            output.write(erd_script::svg::render(&erd.to_dot_with(notation)).as_bytes())
+        }
+        Command::Render {
+            input,
+            backend: Backend::Graphviz,
+            format,
+            engine,
+            notation,
+            output,
+        } => {
+            let erd = to_erd(&load_project(&input, sources)?)?;
+            let rendered = graphviz::render(&erd.to_dot_with(notation), format, engine).map_err(
+                |e| match e {
+                    graphviz::GraphvizError::NotInstalled => Failure::Message(format!(
+                        "{} Use `--backend native` to draw svg without Graphviz.",
+                        e
+                    )),
+                    e => Failure::Message(e.to_string()),
+                },
+            )?;
+            for warning in rendered.warnings.lines().filter(|l| !l.trim().is_empty()) {
+                report_warning(warning.trim_start_matches("Warning: "), color);
+            }
+            output.write(&rendered.output)
+        }
+        Command::Sql {
+            input,
+            mappings,
+            dialect,
+            output,
+        } => {
+            let project = load_project(&input, sources)?;
+            let mut s = String::new();
+            for (path, physical) in to_physicals(&project, &mappings, sources)?.iter() {
+                if let Some(path) = path {
+                    s.push_str(&format!("-- {}\n", path));
+                }
+                let physical = physical.to_physical();
+                let warnings = physical
+                    .check_identifiers(dialect.dialect())
+                    .into_iter()
+                    .chain(physical.check_foreign_keys(dialect.dialect()));
+                for warning in warnings {
+                    report_warning(&warning, color);
+                }
+                physical.write_sql_create(&mut s, dialect.dialect());
+                s.push('\n');
+            }
+            output.write(s.as_bytes())
+        }
+        Command::Fmt {
+            input,
+            check,
+            output,
+        } => {
+            let content = input.read()?;
+            let project = Project::parse(input.name(), content.clone());
+            sources.extend(project.files().iter().cloned());
+            let diagnostics = project.diagnostics();
+            if !diagnostics.is_empty() {
+                return Err(Failure::Diagnostics(diagnostics));
+            }
+            let formatted = erd_script::format::format_source(&content, &project.expressions());
+            if !check {
+                return output.write(formatted.as_bytes());
+            }
+            if content == formatted {
+                return Ok(());
+            }
+            // Differences in trailing lines are reported after the last common line
+            let line = content
+                .lines()
+                .zip(formatted.lines())
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| content.lines().count().min(formatted.lines().count()));
+            Err(Failure::Message(format!(
+                "{} is not formatted, the first difference is on line {}",
+                input.name(),
+                line + 1
+            )))
+        }
+        Command::Export {
+            input,
+            format: ExportFormat::Json,
+            output,
+        } => {
+            let erd = to_erd(&load_project(&input, sources)?)?;
+            let json = serde_json::to_string_pretty(&erd)
+                .map_err(|e| Failure::Message(format!("failed exporting to json: {}", e)))?;
+            output.write(format!("{}\n", json).as_bytes())
+        }
+        Command::Import {
+            input,
+            sqlite,
+            output,
+        } => {
+            let schema = match sqlite {
+                Some(path) => read_sqlite(&path)?,
+                None => {
+                    let input = Input { input };
+                    Schema::from_sql(&input.read()?).map_err(|e| {
+                        Failure::Message(format!("could not read {}:\n{}", input.name(), e))
+                    })?
+                }
+            };
+            let reversed = ReverseEngineered::from(&schema);
+            for warning in reversed.warnings.iter() {
+                report_warning(warning, color);
+            }
+            output.write(reversed.to_script().as_bytes())
+        }
+    }
+}
+
+fn main() -> ExitCode {
     let opts: Opts = Opts::parse();
-    let ast = parse_file(&std::path::Path::new(&opts.file_path)).expect("Failed parsing file");
-    let physical: erd_script::physical::PhysicalDescription = ast.try_into().expect("Error");
-
-    let mut s = String::new();
-    physical
-        .to_physical()
-        .write_sql_create(&mut s, SQL::LibreOfficeBase);
-    println!("{}\n", s);
-
-    let output = compile_dot(&physical.to_dot()).expect("failed converting with dot");
-    std::fs::write(opts.output_path, output.stdout).expect("failed writing svg");
-    if !output.stderr.is_empty() {
-        println!("Error: {:#?}", std::str::from_utf8(&output.stderr));
+    let color = opts.color.enabled();
+    let mut sources = Vec::new();
+    match run(opts.command, &mut sources, color) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Diagnostics(diagnostics)) => {
+            report_diagnostics(diagnostics, &sources, color);
+            ExitCode::FAILURE
+        }
+        Err(Failure::Message(message)) => {
+            report_error(&message, color);
+            ExitCode::FAILURE
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use erd_script::erd::ERD;
-    use std::convert::TryInto;
     #[test]
-    fn compile_examples() -> Result<(), ConsumeError> {
+    fn compile_examples() {
         let paths = std::fs::read_dir("../examples").unwrap();
 
-        for path in paths.filter(|p| {
-            p.as_ref().unwrap().path().extension() == Some(&std::ffi::OsStr::new("erd"))
-        }) {
+        for path in paths
+            .filter(|p| p.as_ref().unwrap().path().extension() == Some(std::ffi::OsStr::new("erd")))
+        {
             let path = path.unwrap().path();
-            let expr = parse_file(&path)?;
-            let erd: Result<ERD, _> = expr.try_into();
+            let project = Project::load(&path);
+            assert_eq!(project.diagnostics(), Vec::new());
+            let erd: Result<ERD, _> = project.expressions().try_into();
             let dot = erd.unwrap().to_dot();
-            let output = compile_dot(&dot).expect("failed converting with dot");
-            let new_path = path.with_extension("svg");
-            std::fs::write(new_path, output.stdout).expect("failed writing svg");
+            assert!(erd_script::svg::render(&dot).ends_with("</svg>\n"));
+            match graphviz::render(&dot, graphviz::Format::Svg, None) {
+                Ok(rendered) => {
+                    let new_path = path.with_extension("svg");
+                    std::fs::write(new_path, rendered.output).expect("failed writing svg");
+                }
+                // The examples are still checked when Graphviz isn't available
+                Err(graphviz::GraphvizError::NotInstalled) => (),
+                Err(e) => panic!("failed converting {} with dot: {}", path.display(), e),
+            }
         }
-
-        Ok(())
     }
 }
@@ -11,58 +11,110 @@ pub struct ERDParser;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ParserNode<'i> {
     pub expr: ParserExpr,
+    /// The span of the name of the expression
     pub span: Span<'i>,
 }
 
+/// ("id" | "attribute", Name, Option<type>, Vec<(modifier, Option<argument>)>, span)
+pub type ParserAttribute = (
+    String,
+    String,
+    Option<String>,
+    Vec<(String, Option<String>)>,
+    ast::Span,
+);
+
+/// (Vec<fk_names>, fk_rel, Option<on delete>, Option<on update>, span)
+pub type ParserForeignKey = (
+    Vec<String>,
+    String,
+    Option<String>,
+    Option<String>,
+    ast::Span,
+);
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ParserExpr {
-    /// (Name, Vec<("id" | "attribute", Name, Option<type>)> )
-    Entity(String, Vec<(String, String, Option<String>)>),
-    /// (Name, Optional label, Vec<RelationMembers>, Vec<("id" | "attribute", Name, Option<type>)>)
+    /// (Name, Vec<("id" | "attribute", Name, Option<type>, Vec<modifier>, span)>, weak)
+    Entity(String, Vec<ParserAttribute>, bool),
+    /// (Name, Optional label, Vec<(cardinality, optionality, entity, Option<role>, span)>, Vec<("id" | "attribute", Name, Option<type>, Vec<modifier>, span)>, identifying)
     Relation(
         String,
         Option<String>,
-        Vec<(String, String, String)>,
-        Vec<(String, String, Option<String>)>,
+        Vec<(String, String, String, Option<String>, ast::Span)>,
+        Vec<ParserAttribute>,
+        bool,
     ),
-    /// (name, entity, Vec<fk_names, fk_rel>)
-    EntityTable(String, String, Vec<(Vec<String>, String)>),
+    /// (name, entity, Vec<(fk_names, fk_rel, on delete, on update, span)>)
+    EntityTable(String, String, Vec<ParserForeignKey>),
     /// (name, relation
     RelationTable(String, String),
+    /// (path)
+    Import(String),
 }
 
-impl<'i> std::convert::From<ParserNode<'i>> for ast::Expr {
-    fn from(node: ParserNode<'i>) -> ast::Expr {
-        node.expr.into()
-    }
-}
-impl<'i> std::convert::From<ParserExpr> for ast::Expr {
-    fn from(expr: ParserExpr) -> ast::Expr {
-        match expr {
-            ParserExpr::Entity(name, attributes) => ast::Expr::Entity(
-                name.into(),
-                attributes.into_iter().map(|a| a.into()).collect(),
-            ),
-            ParserExpr::Relation(name, label_option, members, attributes) => ast::Expr::Relation(
+impl<'i> ParserNode<'i> {
+    fn into_expr(self, file: Option<&str>) -> Result<ast::Expr, Vec<ast::ConversionError>> {
+        let span = ast::Span::from_pest(self.span, file);
+        let mut errors = Vec::new();
+        let expr = match self.expr {
+            ParserExpr::Entity(name, attributes, weak) => ast::Expr::Entity(
                 name.into(),
-                label_option.map(|l| l.into()),
-                members.into_iter().map(|m| m.into()).collect(),
-                attributes.into_iter().map(|m| m.into()).collect(),
+                try_convert_all(attributes, &mut errors),
+                weak,
+                span,
             ),
+            ParserExpr::Relation(name, label_option, members, attributes, identifying) => {
+                ast::Expr::Relation(
+                    name.into(),
+                    label_option,
+                    try_convert_all(members, &mut errors),
+                    try_convert_all(attributes, &mut errors),
+                    identifying,
+                    span,
+                )
+            }
             ParserExpr::EntityTable(name, er, foreign_keys) => ast::Expr::EntityTable(
                 name.into(),
                 er.into(),
-                foreign_keys.into_iter().map(|f| f.into()).collect(),
+                try_convert_all(foreign_keys, &mut errors),
+                span,
             ),
-            ParserExpr::RelationTable(name, er) => ast::Expr::RelationTable(name.into(), er.into()),
+            ParserExpr::RelationTable(name, er) => {
+                ast::Expr::RelationTable(name.into(), er.into(), span)
+            }
+            ParserExpr::Import(path) => ast::Expr::Import(path, span),
+        };
+        if errors.is_empty() {
+            Ok(expr)
+        } else {
+            Err(errors)
         }
     }
 }
 
+/// Converts all items, the errors of the items that can't be converted are added to `errors`
+fn try_convert_all<T, U>(items: Vec<T>, errors: &mut Vec<ast::ConversionError>) -> Vec<U>
+where
+    U: std::convert::TryFrom<T, Error = ast::ConversionError>,
+{
+    items
+        .into_iter()
+        .filter_map(|item| U::try_from(item).map_err(|e| errors.push(e)).ok())
+        .collect()
+}
+
+impl<'i> std::convert::TryFrom<ParserNode<'i>> for ast::Expr {
+    type Error = Vec<ast::ConversionError>;
+    fn try_from(node: ParserNode<'i>) -> Result<ast::Expr, Self::Error> {
+        node.into_expr(None)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConsumeError {
     ERDParseError(Vec<Error<Rule>>),
-    UnknownParseError,
+    ConversionError(Vec<ast::ConversionError>),
 }
 
 impl std::fmt::Display for ConsumeError {
@@ -74,64 +126,177 @@ impl std::fmt::Display for ConsumeError {
                     "Error while parsing ERD:\n{}",
                     errors
                         .iter()
-                        .map(|e| format!("{:?}", e))
+                        .map(|e| format!("{}", e))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )
+            }
+            ConsumeError::ConversionError(errors) => {
+                write!(
+                    f,
+                    "Error while parsing ERD:\n{}",
+                    errors
+                        .iter()
+                        .map(|e| format!("{}", e))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )
             }
-            ConsumeError::UnknownParseError => write!(f, "Unknown parse error, please report."),
         }
     }
 }
 
-fn consume_expression_with_spans(pairs: Pairs<Rule>) -> Result<Vec<ParserNode>, Vec<Error<Rule>>> {
+fn consume_expression_with_spans<'i>(
+    pairs: Pairs<'i, Rule>,
+    file: Option<&str>,
+) -> (Vec<ParserNode<'i>>, Vec<Error<Rule>>) {
     let mut results = Vec::new();
-    for expression in pairs.filter(|pair| pair.as_rule() == Rule::expression) {
-        results.push(consume_expression(expression)?);
+    let mut errors = Vec::new();
+    for pair in pairs {
+        let result = match pair.as_rule() {
+            Rule::expression => consume_expression(pair.into_inner().next().unwrap(), file),
+            Rule::import => consume_expression(pair, file),
+            Rule::invalid => consume_invalid(pair, file),
+            _ => continue,
+        };
+        match result {
+            Ok(node) => results.push(node),
+            Err(e) => errors.extend(e),
+        }
     }
-    Ok(results)
+    (results, errors)
 }
 
-pub fn consume_expressions(pairs: Pairs<Rule>) -> Result<Vec<ast::Expr>, ConsumeError> {
-    let res_res = std::panic::catch_unwind(|| {
-        let expression = consume_expression_with_spans(pairs)?;
-        //let errors = validator::validate_ast(&rules);
-        //if errors.is_empty() {
-        Ok(expression.into_iter().map(|e| e.into()).collect())
-        /*} else {
-            Err(errors)
-        }*/
-    });
-    match res_res {
-        Ok(res) => res.map_err(ConsumeError::ERDParseError),
-        Err(_err) => Err(ConsumeError::UnknownParseError),
+pub fn consume_expressions(pairs: Pairs<Rule>) -> Result<Vec<ast::Expr>, Vec<ConsumeError>> {
+    consume_expressions_with_file(pairs, None)
+}
+
+/// Consumes the expressions, the spans of the resulting nodes refer to `file`.
+/// All errors are returned, syntax errors before conversion errors.
+pub fn consume_expressions_with_file(
+    pairs: Pairs<Rule>,
+    file: Option<&str>,
+) -> Result<Vec<ast::Expr>, Vec<ConsumeError>> {
+    let (expressions, errors) = consume_expressions_recovering(pairs, file);
+    if errors.is_empty() {
+        Ok(expressions)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Consumes the expressions of all well-formed blocks and returns them together with the
+/// errors of the invalid blocks.
+pub fn consume_expressions_recovering(
+    pairs: Pairs<Rule>,
+    file: Option<&str>,
+) -> (Vec<ast::Expr>, Vec<ConsumeError>) {
+    let (nodes, syntax_errors) = consume_expression_with_spans(pairs, file);
+    let mut expressions = Vec::new();
+    let mut conversion_errors = Vec::new();
+    for node in nodes {
+        match node.into_expr(file) {
+            Ok(expr) => expressions.push(expr),
+            Err(e) => conversion_errors.extend(e),
+        }
+    }
+    let mut errors = Vec::new();
+    if !syntax_errors.is_empty() {
+        errors.push(ConsumeError::ERDParseError(syntax_errors));
+    }
+    if !conversion_errors.is_empty() {
+        errors.push(ConsumeError::ConversionError(conversion_errors));
     }
+    (expressions, errors)
 }
 
-fn consume_expression(expression: Pair<Rule>) -> Result<ParserNode, Vec<Error<Rule>>> {
-    let pair = expression.into_inner().next().unwrap();
+/// Parses an invalid block on its own to find out what is wrong with it
+fn consume_invalid<'i>(
+    pair: Pair<'i, Rule>,
+    file: Option<&str>,
+) -> Result<ParserNode<'i>, Vec<Error<Rule>>> {
+    let block = pair.as_span();
+    match ERDParser::parse(Rule::single_expression, block.as_str()) {
+        Err(e) => Err(vec![relocate_error(e, block)]),
+        // A block that parses on its own is always matched as an expression
+        Ok(_) => Err(vec![Error::new_from_span(
+            pest::error::ErrorVariant::CustomError {
+                message: "invalid expression".to_string(),
+            },
+            block,
+        )]),
+    }
+    .map_err(|errors| match file {
+        Some(file) => errors.into_iter().map(|e| e.with_path(file)).collect(),
+        None => errors,
+    })
+}
+
+/// Moves an error of a parse of `block` on its own to the right location in the whole input
+fn relocate_error(error: Error<Rule>, block: Span) -> Error<Rule> {
+    let input = block.get_input();
+    let offset = block.start();
+    match error.location {
+        pest::error::InputLocation::Pos(p) => Error::new_from_pos(
+            error.variant,
+            pest::Position::new(input, offset + p).unwrap_or_else(|| block.start_pos()),
+        ),
+        pest::error::InputLocation::Span((start, end)) => Error::new_from_span(
+            error.variant,
+            Span::new(input, offset + start, offset + end).unwrap_or(block),
+        ),
+    }
+}
+
+fn consume_attribute(attribute: Pair<Rule>, file: Option<&str>) -> ParserAttribute {
+    let span = ast::Span::from_pest(attribute.as_span(), file);
+    let mut pairs = attribute.into_inner().peekable();
+    let r#type = pairs.next().unwrap().as_str().to_string();
+    let name = pairs.next().unwrap().as_str().to_string();
+    let datatype = pairs
+        .next_if(|pair| pair.as_rule() == Rule::datatype)
+        .map(|pair| pair.as_str().to_string());
+    let modifiers = pairs
+        .map(|pair| {
+            let modifier = match pair.as_rule() {
+                Rule::required => "required",
+                Rule::unique => "unique",
+                Rule::default => "default",
+                Rule::check => "check",
+                _ => unreachable!(),
+            };
+            let argument = pair.into_inner().next().map(|p| p.as_str().to_string());
+            (modifier.to_string(), argument)
+        })
+        .collect();
+    (r#type, name, datatype, modifiers, span)
+}
+
+fn consume_expression<'i>(
+    pair: Pair<'i, Rule>,
+    file: Option<&str>,
+) -> Result<ParserNode<'i>, Vec<Error<Rule>>> {
     match pair.as_rule() {
         Rule::entity => {
-            let mut pairs = pair.into_inner();
+            let mut pairs = pair.into_inner().peekable();
+            let weak = pairs.next_if(|p| p.as_rule() == Rule::weak).is_some();
             let pair = pairs.next().unwrap();
             let name = pair.as_str().trim().to_string();
             let mut attributes = Vec::new();
             for attribute in pairs {
-                let mut pairs = attribute.into_inner();
-                attributes.push((
-                    pairs.next().unwrap().as_str().to_string(),
-                    pairs.next().unwrap().as_str().to_string(),
-                    pairs.next().map(|pair| pair.as_str().to_string()),
-                ))
+                attributes.push(consume_attribute(attribute, file))
             }
 
             Ok(ParserNode {
-                expr: ParserExpr::Entity(name, attributes),
+                expr: ParserExpr::Entity(name, attributes, weak),
                 span: pair.as_span(),
             })
         }
         Rule::relation => {
             let mut pairs = pair.into_inner().peekable();
+            let identifying = pairs
+                .next_if(|p| p.as_rule() == Rule::identifying)
+                .is_some();
             let pair = pairs.next().unwrap();
             let name = pair.as_str().trim().to_string();
             let mut members = Vec::new();
@@ -147,41 +312,37 @@ fn consume_expression(expression: Pair<Rule>) -> Result<ParserNode, Vec<Error<Ru
             for item in pairs {
                 match item.as_rule() {
                     Rule::member => {
+                        let span = ast::Span::from_pest(item.as_span(), file);
                         let mut pairs = item.into_inner();
                         members.push((
                             pairs.next().unwrap().as_str().to_string(),
                             pairs.next().unwrap().as_str().to_string(),
                             pairs.next().unwrap().as_str().to_string(),
+                            pairs.next().map(|p| p.as_str().to_string()),
+                            span,
                         ))
                     }
-                    Rule::attribute => {
-                        let mut pairs = item.into_inner();
-                        attributes.push((
-                            pairs.next().unwrap().as_str().to_string(),
-                            pairs.next().unwrap().as_str().to_string(),
-                            pairs.next().map(|pair| pair.as_str().to_string()),
-                        ))
-                    }
+                    Rule::attribute => attributes.push(consume_attribute(item, file)),
                     _ => unreachable!(),
                 }
             }
 
             Ok(ParserNode {
-                expr: ParserExpr::Relation(name, label, members, attributes),
+                expr: ParserExpr::Relation(name, label, members, attributes, identifying),
                 span: pair.as_span(),
             })
         }
         Rule::entity_table => {
             let mut foreign_keys = Vec::new();
             let mut pairs = pair.into_inner().peekable();
-            let pair = pairs.next().unwrap();
-            let name = pair.as_str().to_string();
-            let pair = pairs.next().unwrap();
-            let entity = pair.as_str().to_string();
+            let name_pair = pairs.next().unwrap();
+            let name = name_pair.as_str().to_string();
+            let entity = pairs.next().unwrap().as_str().to_string();
 
             for pair in pairs {
                 match pair.as_rule() {
                     Rule::foreign => {
+                        let span = ast::Span::from_pest(pair.as_span(), file);
                         let mut pairs = pair.into_inner();
                         let names = pairs
                             .next()
@@ -190,7 +351,20 @@ fn consume_expression(expression: Pair<Rule>) -> Result<ParserNode, Vec<Error<Ru
                             .map(|pair| pair.as_str().to_string())
                             .collect::<Vec<_>>();
 
-                        foreign_keys.push((names, pairs.next().unwrap().as_str().to_string()));
+                        let relation = pairs.next().unwrap().as_str().to_string();
+                        let mut on_delete = None;
+                        let mut on_update = None;
+                        for pair in pairs {
+                            let rule = pair.as_rule();
+                            let action = pair.into_inner().next().unwrap().as_str().to_string();
+                            match rule {
+                                Rule::on_delete => on_delete = Some(action),
+                                Rule::on_update => on_update = Some(action),
+                                _ => unreachable!(),
+                            }
+                        }
+
+                        foreign_keys.push((names, relation, on_delete, on_update, span));
                     }
                     _ => unreachable!(),
                 }
@@ -198,25 +372,162 @@ fn consume_expression(expression: Pair<Rule>) -> Result<ParserNode, Vec<Error<Ru
 
             Ok(ParserNode {
                 expr: ParserExpr::EntityTable(name, entity, foreign_keys),
-                span: pair.as_span(),
+                span: name_pair.as_span(),
             })
         }
         Rule::relation_table => {
             let mut pairs = pair.into_inner().peekable();
             let pair = pairs.next().unwrap();
             let name = pair.as_str().to_string();
-            let pair = pairs.next().unwrap();
-            let relation = pair.as_str().to_string();
+            let relation = pairs.next().unwrap().as_str().to_string();
 
             Ok(ParserNode {
                 expr: ParserExpr::RelationTable(name, relation),
                 span: pair.as_span(),
             })
         }
+        Rule::import => {
+            let pair = pair.into_inner().next().unwrap();
+
+            Ok(ParserNode {
+                expr: ParserExpr::Import(pair.as_str().to_string()),
+                span: pair.as_span(),
+            })
+        }
         _ => unreachable!(),
     }
 }
 
+#[allow(clippy::result_large_err)]
 pub fn parse_as_erd(s: &str) -> Result<Pairs<'_, Rule>, pest::error::Error<Rule>> {
     ERDParser::parse(Rule::erd, s)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recover_at_blank_lines() {
+        let source = "entity A\n  atribute x\n\nentity B\n\nrelation R\n  one requird A\n  one optional B\n\nentity C";
+        let (expressions, errors) =
+            consume_expressions_recovering(parse_as_erd(source).unwrap(), None);
+        assert_eq!(expressions.len(), 2);
+        let lines: Vec<_> = match &errors[..] {
+            [ConsumeError::ERDParseError(errors)] => errors
+                .iter()
+                .map(|e| match e.line_col {
+                    pest::error::LineColLocation::Pos((line, _)) => line,
+                    pest::error::LineColLocation::Span((line, _), _) => line,
+                })
+                .collect(),
+            _ => panic!("Expected syntax errors, got {:?}", errors),
+        };
+        assert_eq!(lines, vec![2, 7]);
+    }
+
+    #[test]
+    fn conversion_errors_are_spanned() {
+        let source = "entity A\n  attribute x type varchar(99999999999999999999999)\n\nentity B\n\nrelation R\n  exactly(0) required A\n  exactly(3) optional B";
+        let (expressions, errors) =
+            consume_expressions_recovering(parse_as_erd(source).unwrap(), None);
+        assert_eq!(expressions.len(), 1);
+        match &errors[..] {
+            [ConsumeError::ConversionError(errors)] => {
+                assert!(matches!(
+                    &errors[..],
+                    [
+                        ast::ConversionError::NumberOutOfRange(_, s1),
+                        ast::ConversionError::ZeroCardinality(s2),
+                    ] if s1.line == 2 && s2.line == 7
+                ))
+            }
+            _ => panic!("Expected conversion errors, got {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn attribute_modifiers() {
+        let source =
+            "entity A\n  attribute x type integer required unique default -1 check(x > (0 - 2))";
+        let expressions = consume_expressions(parse_as_erd(source).unwrap()).unwrap();
+        match &expressions[..] {
+            [ast::Expr::Entity(_, attributes, _, _)] => assert_eq!(
+                attributes[0].modifiers,
+                vec![
+                    ast::AttributeModifier::Required,
+                    ast::AttributeModifier::Unique,
+                    ast::AttributeModifier::Default("-1".to_string()),
+                    ast::AttributeModifier::Check("x > (0 - 2)".to_string()),
+                ]
+            ),
+            _ => panic!("Expected an entity, got {:?}", expressions),
+        }
+    }
+
+    #[test]
+    fn exact_cardinality() {
+        let source = "relation R\n  exactly(3) required A\n  one optional B";
+        let expressions = consume_expressions(parse_as_erd(source).unwrap()).unwrap();
+        match &expressions[..] {
+            [ast::Expr::Relation(_, _, members, _, _, _)] => {
+                assert_eq!(members[0].cardinality, ast::RelationCardinality::Exact(3))
+            }
+            _ => panic!("Expected a relation, got {:?}", expressions),
+        }
+    }
+
+    #[test]
+    fn bounded_cardinalities() {
+        let source = "relation R\n  between(2, 11) required A\n  at_least(1) optional B";
+        let expressions = consume_expressions(parse_as_erd(source).unwrap()).unwrap();
+        match &expressions[..] {
+            [ast::Expr::Relation(_, _, members, _, _, _)] => {
+                assert_eq!(
+                    members[0].cardinality,
+                    ast::RelationCardinality::Between(2, 11)
+                );
+                assert_eq!(members[1].cardinality, ast::RelationCardinality::AtLeast(1));
+                assert_eq!(members[0].to_string(), "between(2, 11) required A");
+            }
+            _ => panic!("Expected a relation, got {:?}", expressions),
+        }
+        assert!(consume_expressions(
+            parse_as_erd("relation R\n  between(3, 2) required A\n  one optional B").unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn member_roles() {
+        let source =
+            "relation Mother\n  multiple optional Person as child\n  one optional Person as mother";
+        let expressions = consume_expressions(parse_as_erd(source).unwrap()).unwrap();
+        match &expressions[..] {
+            [ast::Expr::Relation(_, _, members, _, _, _)] => {
+                assert_eq!(members[0].role, Some("child".to_string().into()));
+                assert_eq!(members[1].role, Some("mother".to_string().into()));
+                assert_eq!(members[1].to_string(), "one optional Person as mother");
+            }
+            _ => panic!("Expected a relation, got {:?}", expressions),
+        }
+    }
+
+    #[test]
+    fn weak_entities() {
+        let source = "weak entity Room\n  id number\n\nidentifying relation Contains\n  one required Building\n  multiple optional Room";
+        let expressions = consume_expressions(parse_as_erd(source).unwrap()).unwrap();
+        match &expressions[..] {
+            [ast::Expr::Entity(_, _, true, _), ast::Expr::Relation(_, _, _, _, true, _)] => {
+                assert_eq!(expressions[0].to_string(), "weak entity Room\n  id number");
+                assert!(expressions[1]
+                    .to_string()
+                    .starts_with("identifying relation Contains"));
+            }
+            _ => panic!(
+                "Expected a weak entity and an identifying relation, got {:?}",
+                expressions
+            ),
+        }
+    }
+}
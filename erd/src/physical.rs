@@ -1,12 +1,13 @@
-use crate::ast::{Attribute, AttributeType, DataType};
-use crate::ast::{Expr, ForeignKey, Ident};
-use crate::erd::{ERDError, ERD};
-use crate::sql::SQL;
+use crate::ast::{Attribute, AttributeModifier, AttributeType, DataType};
+use crate::ast::{
+    Expr, ForeignKey, Ident, ReferentialAction, RelationMember, RelationOptionality, Span,
+};
+use crate::erd::{ERDError, ERDFromScriptError, ERD};
+use crate::sql::{EnumStrategy, SqlDialect};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fmt::Write;
-use std::iter::FromIterator;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TableDescription {
@@ -39,6 +40,12 @@ impl TableDescription {
             TableDescription::Relation(r) => r.relation.clone(),
         }
     }
+    pub fn span(&self) -> Span {
+        match self {
+            TableDescription::Entity(e) => e.span.clone(),
+            TableDescription::Relation(r) => r.span.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -46,40 +53,74 @@ pub struct EntityTableDescription {
     name: Ident,
     entity: Ident,
     foreign_keys: Vec<ForeignKey>,
+    span: Span,
 }
 
 impl EntityTableDescription {
     pub fn to_table(&self, erd: &ERD) -> Table {
+        // Nothing limits how many rows of a weak entity belong to the same owner
+        let owner_note = erd.get_owner(&self.entity).and_then(|(relation, owner)| {
+            let (own, _) = relation.foreign_key_members(&self.entity).unwrap();
+            let (min, max) = own.bounds();
+            (min != 0 || max.is_some())
+                .then(|| unenforced_bounds(&relation.name(), own, &[owner.name()]))
+        });
+        let notes = owner_note
+            .into_iter()
+            .chain(self.foreign_keys.iter().filter_map(|c| {
+                let relation = erd.get_relation(c.relation.to_owned()).unwrap();
+                let (own, other) = relation.foreign_key_members(&self.entity).unwrap();
+                // Only a single unique column limits how many rows refer to the same row
+                let (min, max) = own.bounds();
+                let is_unique = max == Some(1) && c.attribute_names.len() == 1;
+                (min != 0 || (max.is_some() && !is_unique))
+                    .then(|| unenforced_bounds(&relation.name(), own, &[other.name()]))
+            }))
+            .collect();
         Table {
             name: self.name.clone(),
+            // A weak entity starts with the key of its owner
             columns: erd
-                .get_entity_attributes(self.entity.clone())
+                .get_owner_key(&self.entity)
                 .into_iter()
+                .chain(erd.get_entity_attributes(self.entity.clone()))
                 .chain(self.foreign_keys.iter().flat_map(|c| {
-                    let other_member = erd
-                        .get_relation(c.relation.to_owned())
-                        .unwrap()
-                        .find_other_member(self.entity.clone());
-                    erd.get_entity_ids(other_member)
+                    let relation = erd.get_relation(c.relation.to_owned()).unwrap();
+                    let (own_member, other_member) =
+                        relation.foreign_key_members(&self.entity).unwrap();
+                    // Every row has to refer to a required member
+                    let mut modifiers = match other_member.optionality {
+                        RelationOptionality::Required => vec![AttributeModifier::Required],
+                        RelationOptionality::Optional => Vec::new(),
+                    };
+                    // At most one row can refer to the same row
+                    if own_member.cardinality.is_one() && c.attribute_names.len() == 1 {
+                        modifiers.push(AttributeModifier::Unique);
+                    }
+                    erd.get_entity_key(other_member.entity.clone())
                         .into_iter()
                         .zip(c.attribute_names.iter())
                         .map(move |(a, a_name)| Attribute {
                             ident: a_name.clone(),
                             r#type: AttributeType::Normal,
                             datatype: a.get_data_type().map(|d| d.foreign_key_type()),
+                            modifiers: modifiers.clone(),
+                            span: c.span.clone(),
                         })
-                        .chain(erd.get_relation_attributes(c.relation.clone()).into_iter())
+                        .chain(erd.get_relation_attributes(c.relation.clone()))
                 }))
                 .map(|c| TableColumn {
                     name: c.get_ident(),
                     datatype: c.get_data_type().unwrap(),
+                    modifiers: c.modifiers,
                 })
                 .collect(),
             primary_key_parts: erd
-                .get_entity_ids(self.entity.clone())
+                .get_entity_key(self.entity.clone())
                 .into_iter()
                 .map(|a| a.get_ident())
                 .collect(),
+            notes,
         }
     }
     pub fn check_entity(&self, erd: &ERD) -> bool {
@@ -91,50 +132,98 @@ impl EntityTableDescription {
 pub struct RelationTableDescription {
     name: Ident,
     relation: Ident, // entity or relation where it comes from
+    span: Span,
+}
+
+/// The columns of a relation table that refer to one member of the relation
+struct MemberColumns {
+    entity: Ident,
+    columns: Vec<Attribute>,
+    ids: Vec<Attribute>,
+    in_key: bool,
 }
 
 impl RelationTableDescription {
+    /// The foreign key columns of every member, named after its role or else its entity
+    fn member_columns(&self, erd: &ERD) -> Vec<MemberColumns> {
+        let relation = erd.get_relation(self.relation.clone()).unwrap();
+        relation
+            .get_members_with_key()
+            .into_iter()
+            .map(|(member, in_key)| {
+                let entity = member.entity;
+                let prefix = member.role.unwrap_or_else(|| entity.clone());
+                let ids = erd.get_entity_key(entity.clone());
+                let columns = ids
+                    .iter()
+                    .map(|a| Attribute {
+                        // Every member of a row is known, the key columns are never null anyway
+                        modifiers: if in_key {
+                            Vec::new()
+                        } else {
+                            vec![AttributeModifier::Required]
+                        },
+                        ..a.renamed(format!("{}_{}", prefix, a.ident).into())
+                    })
+                    .collect();
+                MemberColumns {
+                    entity,
+                    columns,
+                    ids,
+                    in_key,
+                }
+            })
+            .collect()
+    }
+
     pub fn to_table(&self, erd: &ERD) -> Table {
         let relation = erd.get_relation(self.relation.clone()).unwrap();
-        let members = relation.get_members();
-        let mut used_members: HashSet<Ident> = HashSet::new();
-        let foreign_key_parts: Vec<_> = members
+        let members_with_key = relation.get_members_with_key();
+        let members: Vec<_> = members_with_key.iter().map(|(m, _)| m.clone()).collect();
+        let notes = members_with_key
             .iter()
-            .flat_map(|e| {
-                let postfix = if used_members.contains(&e) {
-                    "2" // TODO improve if other than binary relation are supported
-                } else {
-                    used_members.insert(e.to_owned());
-                    ""
-                };
-                erd.get_entity_ids(e.to_owned()).into_iter().map(move |a| {
-                    a.renamed(format!("{}_{}{}", e, a.ident.to_string(), postfix).into())
+            .enumerate()
+            .filter_map(|(i, (member, in_key))| {
+                // Only the absence of rows and the key are enforced
+                let (min, max) = member.bounds();
+                let max_enforced = max.map_or(true, |m| m == 1 && !in_key);
+                (min != 0 || !max_enforced).then(|| {
+                    let others: Vec<_> = members
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| i != *j)
+                        .map(|(_, m)| m.name())
+                        .collect();
+                    unenforced_bounds(&self.relation, member, &others)
                 })
             })
             .collect();
-        let primary_key_parts: Vec<_> = foreign_key_parts
-            .clone()
-            .into_iter()
-            .chain(erd.get_relation_ids(self.relation.clone()).into_iter())
+        let member_columns = self.member_columns(erd);
+        let primary_key_parts: Vec<_> = member_columns
+            .iter()
+            .filter(|m| m.in_key)
+            .flat_map(|m| m.columns.iter().map(|a| a.get_ident()))
+            .chain(
+                erd.get_relation_ids(self.relation.clone())
+                    .into_iter()
+                    .map(|a| a.get_ident()),
+            )
             .collect();
 
         Table {
             name: self.name.clone(),
-            columns: foreign_key_parts
+            columns: member_columns
                 .into_iter()
-                .chain(
-                    erd.get_relation_attributes(self.relation.clone())
-                        .into_iter(),
-                )
+                .flat_map(|m| m.columns)
+                .chain(erd.get_relation_attributes(self.relation.clone()))
                 .map(|c| TableColumn {
                     name: c.get_ident(),
                     datatype: c.get_data_type().unwrap().foreign_key_type(),
+                    modifiers: c.modifiers,
                 })
                 .collect(),
-            primary_key_parts: primary_key_parts
-                .into_iter()
-                .map(|a| a.get_ident())
-                .collect(),
+            primary_key_parts,
+            notes,
         }
     }
     pub fn check_relation(&self, erd: &ERD) -> bool {
@@ -142,21 +231,110 @@ impl RelationTableDescription {
     }
 }
 
+/// A note that the bounds of `member` are not enforced, they count the member for every
+/// combination of `others`
+fn unenforced_bounds(relation: &Ident, member: &RelationMember, others: &[Ident]) -> String {
+    let (min, max) = member.bounds();
+    let others: Vec<_> = others.iter().map(|o| o.0.as_str()).collect();
+    format!(
+        "Relation {}: ({},{}) {} per {} is not enforced.",
+        relation,
+        min,
+        max.map_or("n".to_string(), |m| m.to_string()),
+        member.name(),
+        others.join(" and ")
+    )
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TableColumn {
     name: Ident,
     datatype: DataType,
-    // TODO unique, nullable, type ...
+    modifiers: Vec<AttributeModifier>,
 }
 
 impl TableColumn {
-    fn write_sql_create_lines(&self, s: &mut String, sql: SQL) {
-        write!(
-            s,
-            "{} {},",
-            sql.to_column_ident(&self.name),
-            sql.to_data_type(&self.datatype)
-        );
+    /// `is_key` tells whether the column is the only column of the primary key,
+    /// `in_key` whether it is one of its columns
+    fn to_sql_definition(
+        &self,
+        sql: &dyn SqlDialect,
+        enum_types: &EnumTypes,
+        is_key: bool,
+        in_key: bool,
+    ) -> String {
+        let datatype = match &self.datatype {
+            DataType::Enum(options) if sql.enum_strategy() == EnumStrategy::NamedType => {
+                sql.to_ident(enum_types.name(options))
+            }
+            // The column can't be the primary key on its own
+            d if sql.declares_primary_key(d) && !is_key => sql.to_data_type(&d.foreign_key_type()),
+            d => sql.to_data_type(d),
+        };
+        let mut definition = vec![sql.to_ident(&self.name), datatype];
+        for modifier in self.modifiers.iter() {
+            if let AttributeModifier::Default(value) = modifier {
+                definition.push(format!("DEFAULT {}", sql.to_default_value(value)));
+            }
+        }
+        if self.modifiers.contains(&AttributeModifier::Required)
+            || (in_key && sql.requires_not_null_key())
+        {
+            definition.push("NOT NULL".to_string());
+        }
+        if self.modifiers.contains(&AttributeModifier::Unique) {
+            definition.push("UNIQUE".to_string());
+        }
+        if let DataType::Enum(options) = &self.datatype {
+            if sql.enum_strategy() == EnumStrategy::Check {
+                definition.push(sql.to_enum_check(&self.name, options));
+            }
+        }
+        for modifier in self.modifiers.iter() {
+            if let AttributeModifier::Check(expression) = modifier {
+                definition.push(format!("CHECK ({})", expression));
+            }
+        }
+        definition.extend(sql.to_column_constraint(&self.name, &self.datatype));
+        definition.join(" ")
+    }
+}
+
+/// The enum types of one sql file, named after the first column that uses them. A number is
+/// added when the name is already used by a table or another type, like `a_b_c_2` for column
+/// `b_c` of table `a` next to column `c` of table `a_b`.
+struct EnumTypes(Vec<(Ident, Vec<String>)>);
+
+impl EnumTypes {
+    fn new(tables: &[Table]) -> Self {
+        // Unquoted names are case insensitive
+        let mut used: HashSet<String> = tables.iter().map(|t| t.name.0.to_lowercase()).collect();
+        let mut types: Vec<(Ident, Vec<String>)> = Vec::new();
+        for table in tables.iter() {
+            for column in table.columns.iter() {
+                if let DataType::Enum(options) = &column.datatype {
+                    if types.iter().all(|(_, o)| o != options) {
+                        let base = format!("{}_{}", table.name, column.name);
+                        let name = std::iter::once(base.clone())
+                            .chain((2..).map(|i| format!("{}_{}", base, i)))
+                            .find(|n| !used.contains(&n.to_lowercase()))
+                            .expect("there is always an unused number");
+                        used.insert(name.to_lowercase());
+                        types.push((name.into(), options.clone()));
+                    }
+                }
+            }
+        }
+        Self(types)
+    }
+
+    fn name(&self, options: &[String]) -> &Ident {
+        &self
+            .0
+            .iter()
+            .find(|(_, o)| o == options)
+            .expect("All enums of the tables have a type")
+            .0
     }
 }
 
@@ -165,36 +343,45 @@ pub struct Table {
     name: Ident,
     columns: Vec<TableColumn>,
     primary_key_parts: Vec<Ident>,
+    /// Constraints of the ERD that the sql can't enforce
+    notes: Vec<String>,
 }
 
 impl Table {
-    fn write_sql_create(&self, s: &mut String, sql: SQL) {
-        let mut additional_definitions = Vec::new();
+    /// `foreign_keys` are the foreign keys that are declared in the table itself
+    fn write_sql_create(
+        &self,
+        s: &mut String,
+        sql: &dyn SqlDialect,
+        enum_types: &EnumTypes,
+        foreign_keys: &[&ForeignKeyConstraint],
+    ) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        let mut has_key_column = false;
 
-        write!(s, "CREATE TABLE {} (\n", self.name);
+        for note in self.notes.iter() {
+            writeln!(s, "-- {}", note)?;
+        }
+        writeln!(s, "CREATE TABLE {} (", sql.to_ident(&self.name))?;
         for col in self.columns.iter() {
-            col.write_sql_create_lines(s, sql);
-            write!(s, "\n");
-            if let Some(x) = sql.to_additional_definitions(&col.datatype) {
-                additional_definitions.push(x);
-            }
+            let is_key = self.primary_key_parts == [col.name.clone()];
+            has_key_column |= is_key && sql.declares_primary_key(&col.datatype);
+            let in_key = self.primary_key_parts.contains(&col.name);
+            lines.push(col.to_sql_definition(sql, enum_types, is_key, in_key));
         }
-        write!(
-            s,
-            "PRIMARY KEY ({})\n",
-            self.primary_key_parts
-                .iter()
-                .map(|p| sql.to_column_ident(p))
-                .collect::<Vec<_>>()
-                .join(","),
-        );
-        write!(s, ");");
-        if !additional_definitions.is_empty() {
-            for def in additional_definitions {
-                writeln!(s, "");
-                write!(s, "{}", def);
-            }
+        if !has_key_column && !self.primary_key_parts.is_empty() {
+            lines.push(format!(
+                "PRIMARY KEY ({})",
+                self.primary_key_parts
+                    .iter()
+                    .map(|p| sql.to_ident(p))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ));
         }
+        lines.extend(foreign_keys.iter().map(|f| f.to_sql_clause(sql)));
+        writeln!(s, "{}", lines.join(",\n"))?;
+        write!(s, "){}", sql.statement_terminator())
     }
 }
 
@@ -204,11 +391,17 @@ pub enum Constraint {
 }
 
 impl Constraint {
-    fn write_sql_create(&self, s: &mut String, sql: SQL) {
+    fn write_sql_create(&self, s: &mut String, sql: &dyn SqlDialect) -> std::fmt::Result {
         match self {
             Self::ForeignKey(f) => f.write_sql_create(s, sql),
         }
     }
+
+    fn is_inline(&self, sql: &dyn SqlDialect) -> bool {
+        match self {
+            Self::ForeignKey(_) => sql.has_inline_foreign_keys(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -217,26 +410,43 @@ pub struct ForeignKeyConstraint {
     column_names: Vec<Ident>,
     other_table_name: Ident,
     other_table_column_names: Vec<Ident>,
+    on_delete: Option<ReferentialAction>,
+    on_update: Option<ReferentialAction>,
 }
 
 impl ForeignKeyConstraint {
-    fn write_sql_create(&self, s: &mut String, sql: SQL) {
+    fn write_sql_create(&self, s: &mut String, sql: &dyn SqlDialect) -> std::fmt::Result {
         write!(
             s,
-            "ALTER TABLE {} ADD FOREIGN KEY ({}) REFERENCES {}({});",
-            self.table_name,
+            "ALTER TABLE {} ADD {}{}",
+            sql.to_ident(&self.table_name),
+            self.to_sql_clause(sql),
+            sql.statement_terminator()
+        )
+    }
+
+    fn to_sql_clause(&self, sql: &dyn SqlDialect) -> String {
+        let mut clause = format!(
+            "FOREIGN KEY ({}) REFERENCES {}({})",
             self.column_names
                 .iter()
-                .map(|a| sql.to_column_ident(a))
+                .map(|a| sql.to_ident(a))
                 .collect::<Vec<_>>()
                 .join(","),
-            self.other_table_name,
+            sql.to_ident(&self.other_table_name),
             self.other_table_column_names
                 .iter()
-                .map(|a| sql.to_column_ident(a))
+                .map(|a| sql.to_ident(a))
                 .collect::<Vec<_>>()
                 .join(","),
         );
+        if let Some(action) = self.on_delete.as_ref().and_then(|a| sql.to_on_delete(a)) {
+            clause.push_str(&format!(" ON DELETE {}", action));
+        }
+        if let Some(action) = self.on_update.as_ref().and_then(|a| sql.to_on_update(a)) {
+            clause.push_str(&format!(" ON UPDATE {}", action));
+        }
+        clause
     }
 }
 
@@ -247,15 +457,87 @@ pub struct Physical {
 }
 
 impl Physical {
-    pub fn write_sql_create(&self, s: &mut String, sql: SQL) {
-        for col in self.tables.iter() {
-            col.write_sql_create(s, sql);
-            write!(s, "\n");
+    pub fn write_sql_create(&self, s: &mut String, sql: &dyn SqlDialect) {
+        self.write_sql(s, sql)
+            .expect("Writing to a String never fails")
+    }
+
+    fn write_sql(&self, s: &mut String, sql: &dyn SqlDialect) -> std::fmt::Result {
+        let enum_types = EnumTypes::new(&self.tables);
+        if sql.enum_strategy() == EnumStrategy::NamedType {
+            for (name, options) in enum_types.0.iter() {
+                writeln!(s, "{}", sql.to_enum_type(name, options))?;
+            }
+        }
+        for table in self.tables.iter() {
+            let foreign_keys: Vec<_> = self
+                .constraints
+                .iter()
+                .filter(|c| c.is_inline(sql))
+                .map(|c| match c {
+                    Constraint::ForeignKey(f) => f,
+                })
+                .filter(|f| f.table_name == table.name)
+                .collect();
+            table.write_sql_create(s, sql, &enum_types, &foreign_keys)?;
+            writeln!(s)?;
+        }
+        for constraint in self.constraints.iter().filter(|c| !c.is_inline(sql)) {
+            constraint.write_sql_create(s, sql)?;
+            writeln!(s)?;
         }
-        for constraint in self.constraints.iter() {
-            constraint.write_sql_create(s, sql);
-            write!(s, "\n");
+        Ok(())
+    }
+
+    /// Warnings for the table and column names that are reserved words of the dbms,
+    /// they are quoted in the sql
+    pub fn check_identifiers(&self, sql: &dyn SqlDialect) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for table in self.tables.iter() {
+            if sql.is_reserved(&table.name.0) {
+                warnings.push(format!("The table name {} is a reserved word.", table.name));
+            }
+            for column in table.columns.iter() {
+                if sql.is_reserved(&column.name.0) {
+                    warnings.push(format!(
+                        "The column name {} of table {} is a reserved word.",
+                        column.name, table.name
+                    ));
+                }
+            }
         }
+        warnings
+    }
+
+    /// Warnings for the referential actions that the dbms doesn't support, they are left out
+    /// of the sql
+    pub fn check_foreign_keys(&self, sql: &dyn SqlDialect) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for Constraint::ForeignKey(f) in self.constraints.iter() {
+            let actions = [
+                (
+                    "delete",
+                    &f.on_delete,
+                    f.on_delete.as_ref().map(|a| sql.to_on_delete(a)),
+                ),
+                (
+                    "update",
+                    &f.on_update,
+                    f.on_update.as_ref().map(|a| sql.to_on_update(a)),
+                ),
+            ];
+            for (event, action, converted) in actions {
+                if let (Some(action), Some(None)) = (action, converted) {
+                    if action != &ReferentialAction::Restrict {
+                        warnings.push(format!(
+                            "On {} {} of the foreign key to {} in table {} is not supported, it is left out.",
+                            event, action, f.other_table_name, f.table_name
+                        ));
+                    }
+                }
+            }
+        }
+        warnings
     }
 }
 
@@ -273,23 +555,38 @@ impl PhysicalDescription {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum PhysicalFromScriptError {
-    ParsingError(crate::parser::ConsumeError),
+    ParsingError(Vec<crate::parser::ConsumeError>),
     ERDError(Vec<ERDError>),
     PhysicalError(Vec<PhysicalError>),
 }
 
 impl PhysicalDescription {
     pub fn from_script(content: &str) -> Result<Self, PhysicalFromScriptError> {
-        let pairs = crate::parser::parse_as_erd(&content).map_err(|e| {
-            PhysicalFromScriptError::ParsingError(crate::parser::ConsumeError::ERDParseError(vec![
-                e,
-            ]))
+        let pairs = crate::parser::parse_as_erd(content).map_err(|e| {
+            PhysicalFromScriptError::ParsingError(vec![crate::parser::ConsumeError::ERDParseError(
+                vec![e],
+            )])
         })?;
         let asts = crate::parser::consume_expressions(pairs)
             .map_err(PhysicalFromScriptError::ParsingError)?;
         let res: Result<Self, PhysicalERDError> = asts.try_into();
         res.map_err(|e| e.into())
     }
+
+    /// Maps the ERD described by `erd` with the tables described by `physical`
+    pub fn from_scripts(erd: &str, physical: &str) -> Result<Self, PhysicalFromScriptError> {
+        let erd = ERD::from_script(erd)?;
+        let pairs = crate::parser::parse_as_erd(physical).map_err(|e| {
+            PhysicalFromScriptError::ParsingError(vec![crate::parser::ConsumeError::ERDParseError(
+                vec![e],
+            )])
+        })?;
+        let asts = crate::parser::consume_expressions(pairs)
+            .map_err(PhysicalFromScriptError::ParsingError)?;
+        (erd, asts)
+            .try_into()
+            .map_err(PhysicalFromScriptError::PhysicalError)
+    }
 }
 
 impl PhysicalDescription {
@@ -298,39 +595,67 @@ impl PhysicalDescription {
             .erd
             .get_missing_datatypes()
             .into_iter()
-            .map(|(e, a)| PhysicalError::MissingDataTypeFromAttributeInEntityOrRelation(e, a))
+            .map(|(e, a, span)| {
+                PhysicalError::MissingDataTypeFromAttributeInEntityOrRelation(e, a, span)
+            })
+            .chain(
+                self.erd
+                    .get_empty_enums()
+                    .into_iter()
+                    .map(|(e, a, span)| PhysicalError::EnumWithoutValues(e, a, span)),
+            )
             .collect();
         let mut converted_entities_relations: HashSet<Ident> = HashSet::new();
         let mut table_names: HashSet<Ident> = HashSet::new();
         for t in self.tables.iter() {
             if table_names.contains(&t.name()) {
-                errors.push(PhysicalError::DuplicateTableName(t.name().clone()));
+                errors.push(PhysicalError::DuplicateTableName(t.name(), t.span()));
             } else {
-                table_names.insert(t.name().clone());
+                table_names.insert(t.name());
             }
             if !t.check_entity_or_relation(&self.erd) {
                 errors.push(PhysicalError::UnknownEntityOrRelationInTable(
                     t.er(),
                     t.name(),
+                    crate::diagnostic::suggest(&t.er(), self.erd.get_idents().iter()),
+                    t.span(),
                 ))
             } else if converted_entities_relations.contains(&t.er()) {
-                errors.push(PhysicalError::ConvertedMoreThanOnce(t.er()));
+                errors.push(PhysicalError::ConvertedMoreThanOnce(t.er(), t.span()));
             } else {
                 converted_entities_relations.insert(t.er());
                 if let TableDescription::Entity(et) = t {
-                    let mut column_names: HashSet<Ident> = HashSet::from_iter(
-                        self.erd
-                            .get_entity_attributes(t.er())
-                            .into_iter()
-                            .map(|a| a.get_ident()),
-                    );
+                    let mut column_names: HashSet<Ident> = HashSet::new();
+                    // The owner of a weak entity is referred to by the key of the table
+                    if let Some((owner_relation, _)) = self.erd.get_owner(&et.entity) {
+                        if !converted_entities_relations.insert(owner_relation.name()) {
+                            errors.push(PhysicalError::ConvertedMoreThanOnce(
+                                owner_relation.name(),
+                                t.span(),
+                            ));
+                        }
+                    }
+                    for attribute in self
+                        .erd
+                        .get_owner_key(&et.entity)
+                        .into_iter()
+                        .chain(self.erd.get_entity_attributes(t.er()))
+                    {
+                        if !column_names.insert(attribute.get_ident()) {
+                            errors.push(PhysicalError::DuplicateColumnNameInTable(
+                                attribute.get_ident(),
+                                t.name(),
+                                t.span(),
+                            ));
+                        }
+                    }
                     for foreign_key in et.foreign_keys.iter() {
-                        // TODO: Check if amount of keys equals amount of PKs
                         for name in foreign_key.attribute_names.iter() {
-                            if column_names.contains(&name) {
+                            if column_names.contains(name) {
                                 errors.push(PhysicalError::DuplicateColumnNameInTable(
                                     name.clone(),
                                     t.name(),
+                                    foreign_key.span.clone(),
                                 ));
                             } else {
                                 column_names.insert(name.clone());
@@ -338,27 +663,55 @@ impl PhysicalDescription {
                         }
                         let relation = self.erd.get_relation(foreign_key.relation.clone());
                         if let Some(r) = relation {
-                            if r.degree() != 2 {
-                                errors.push(PhysicalError::UnsupportedRelationDegree(r.name()));
-                            } else if r.can_work_with_foreign_key(foreign_key.relation.clone()) {
+                            if r.can_work_with_foreign_key(et.entity.clone()) {
                                 if converted_entities_relations.contains(&foreign_key.relation) {
                                     errors.push(PhysicalError::ConvertedMoreThanOnce(
                                         foreign_key.relation.clone(),
+                                        foreign_key.span.clone(),
                                     ));
                                 } else {
                                     converted_entities_relations
                                         .insert(foreign_key.relation.clone());
                                 }
+                                // Every column of the referenced key needs a name
+                                let key: Vec<_> = self
+                                    .erd
+                                    .get_entity_key(r.find_other(et.entity.clone()).entity.clone())
+                                    .into_iter()
+                                    .map(|a| a.get_ident())
+                                    .collect();
+                                if foreign_key.attribute_names.len() != key.len() {
+                                    errors.push(PhysicalError::WrongAmountOfForeignKeyColumns(
+                                        foreign_key.relation.clone(),
+                                        t.name(),
+                                        key,
+                                        foreign_key.span.clone(),
+                                    ));
+                                }
+                                let sets_null = [&foreign_key.on_delete, &foreign_key.on_update]
+                                    .contains(&&Some(ReferentialAction::SetNull));
+                                if sets_null
+                                    && r.find_other(et.entity.clone()).optionality
+                                        == RelationOptionality::Required
+                                {
+                                    errors.push(PhysicalError::SetNullOnRequiredForeignKey(
+                                        foreign_key.relation.clone(),
+                                        t.name(),
+                                        foreign_key.span.clone(),
+                                    ));
+                                }
                             } else {
                                 errors.push(PhysicalError::ImpossibleForeignKey(
                                     foreign_key.relation.clone(),
                                     t.name(),
+                                    foreign_key.span.clone(),
                                 ))
                             }
                         } else {
                             errors.push(PhysicalError::ForeignKeyToEntityInTable(
                                 foreign_key.relation.clone(),
                                 t.name(),
+                                foreign_key.span.clone(),
                             ));
                         }
                     }
@@ -367,14 +720,16 @@ impl PhysicalDescription {
         }
 
         let erd_entities_relations = self.erd.get_idents();
-        let forgotten: HashSet<_> = erd_entities_relations
+        let mut forgotten: Vec<(Ident, Span)> = erd_entities_relations
             .difference(&converted_entities_relations)
+            .map(|item| {
+                let span = self.erd.get_span(item.clone()).unwrap_or_default();
+                (item.clone(), span)
+            })
             .collect();
-        for item in forgotten.iter() {
-            errors.push(PhysicalError::ForgottenEntityOrRelation({
-                let reference: &Ident = *item;
-                reference.clone()
-            }));
+        forgotten.sort_by_key(|(_, span)| span.start);
+        for (item, span) in forgotten.into_iter() {
+            errors.push(PhysicalError::ForgottenEntityOrRelation(item, span));
         }
 
         errors
@@ -395,12 +750,36 @@ impl PhysicalDescription {
 
             match t {
                 TableDescription::Entity(et) => {
+                    // Rows of a weak entity can't exist without their owner
+                    if let Some((_, owner)) = self.erd.get_owner(&et.entity) {
+                        constraints.push(Constraint::ForeignKey(ForeignKeyConstraint {
+                            table_name: t.name(),
+                            column_names: self
+                                .erd
+                                .get_owner_key(&et.entity)
+                                .into_iter()
+                                .map(|a| a.get_ident())
+                                .collect(),
+                            other_table_name: entity_name_to_table_name
+                                .get(&owner.entity)
+                                .unwrap()
+                                .to_owned(),
+                            other_table_column_names: self
+                                .erd
+                                .get_entity_key(owner.entity.clone())
+                                .into_iter()
+                                .map(|a| a.get_ident())
+                                .collect(),
+                            on_delete: Some(ReferentialAction::Cascade),
+                            on_update: None,
+                        }));
+                    }
                     for foreign_key in et.foreign_keys.iter() {
                         let other_entity = self
                             .erd
                             .get_relation(foreign_key.relation.clone())
                             .unwrap()
-                            .find_other_member(et.entity.clone()); // TODO renamings? & more than degree 2
+                            .find_other_member(et.entity.clone());
                         constraints.push(Constraint::ForeignKey(ForeignKeyConstraint {
                             table_name: t.name(),
                             column_names: foreign_key.attribute_names.clone(),
@@ -410,41 +789,31 @@ impl PhysicalDescription {
                                 .to_owned(),
                             other_table_column_names: self
                                 .erd
-                                .get_entity_ids(other_entity.clone())
+                                .get_entity_key(other_entity.clone())
                                 .into_iter()
                                 .map(|a| a.get_ident())
                                 .collect(),
+                            on_delete: foreign_key.on_delete.clone(),
+                            on_update: foreign_key.on_update.clone(),
                         }));
                     }
                 }
                 TableDescription::Relation(r) => {
-                    let relation = self.erd.get_relation(r.relation.clone()).unwrap();
-                    let mut used_members: HashSet<Ident> = HashSet::new(); // TODO: reduce duplication?
-                    for member in relation.get_members().iter() {
-                        let other_table_column_names: Vec<_> = self
-                            .erd
-                            .get_entity_ids(member.clone())
-                            .into_iter()
-                            .map(|a| a.get_ident().clone())
-                            .collect();
-
-                        let postfix = if used_members.contains(&member) {
-                            "2" // TODO improve if other than binary relation are supported
-                        } else {
-                            used_members.insert(member.to_owned());
-                            ""
-                        };
+                    for member in r.member_columns(&self.erd) {
                         constraints.push(Constraint::ForeignKey(ForeignKeyConstraint {
                             table_name: t.name(),
+                            column_names: member.columns.iter().map(|a| a.get_ident()).collect(),
                             other_table_name: entity_name_to_table_name
-                                .get(&member)
+                                .get(&member.entity)
                                 .unwrap()
                                 .to_owned(),
-                            column_names: other_table_column_names
+                            other_table_column_names: member
+                                .ids
                                 .iter()
-                                .map(|n| format!("{}_{}{}", member, n, postfix).into())
+                                .map(|a| a.get_ident())
                                 .collect(),
-                            other_table_column_names,
+                            on_delete: None,
+                            on_update: None,
                         }));
                     }
                 }
@@ -463,32 +832,51 @@ impl std::convert::TryFrom<Vec<Expr>> for PhysicalDescription {
     fn try_from(v: Vec<Expr>) -> Result<Self, Self::Error> {
         let erd: ERD = v.clone().try_into().map_err(PhysicalERDError::ERD)?;
         let tables = v
-            .iter()
+            .into_iter()
+            .filter(|expr| !matches!(expr, Expr::Entity(..) | Expr::Relation(..)))
+            .collect();
+        (erd, tables).try_into().map_err(PhysicalERDError::Physical)
+    }
+}
+
+/// Maps an ERD to tables with the table expressions of a (separate) physical mapping
+impl std::convert::TryFrom<(ERD, Vec<Expr>)> for PhysicalDescription {
+    type Error = Vec<PhysicalError>;
+    fn try_from((erd, v): (ERD, Vec<Expr>)) -> Result<Self, Self::Error> {
+        let mut errors = Vec::new();
+        let tables = v
+            .into_iter()
             .filter_map(|expr| match expr {
-                Expr::EntityTable(name, entity, foreign_keys) => {
+                Expr::EntityTable(name, entity, foreign_keys, span) => {
                     Some(TableDescription::Entity(EntityTableDescription {
-                        name: name.clone(),
-                        entity: entity.clone(),
-                        foreign_keys: foreign_keys.clone(),
+                        name,
+                        entity,
+                        foreign_keys,
+                        span,
                     }))
                 }
-                Expr::RelationTable(name, relation) => {
+                Expr::RelationTable(name, relation, span) => {
                     Some(TableDescription::Relation(RelationTableDescription {
-                        name: name.clone(),
-                        relation: relation.clone(),
+                        name,
+                        relation,
+                        span,
                     }))
                 }
-                _ => None,
+                Expr::Entity(name, _, _, span) | Expr::Relation(name, _, _, _, _, span) => {
+                    errors.push(PhysicalError::DefinitionInPhysicalMapping(name, span));
+                    None
+                }
+                Expr::Import(_, _) => None,
             })
             .collect();
 
         let p = PhysicalDescription { erd, tables };
 
-        let validation = p.validate();
-        if validation.is_empty() {
+        errors.extend(p.validate());
+        if errors.is_empty() {
             Ok(p)
         } else {
-            Err(PhysicalERDError::Physical(validation))
+            Err(errors)
         }
     }
 }
@@ -508,71 +896,608 @@ impl std::convert::From<PhysicalERDError> for PhysicalFromScriptError {
     }
 }
 
+impl std::convert::From<ERDFromScriptError> for PhysicalFromScriptError {
+    fn from(v: ERDFromScriptError) -> Self {
+        match v {
+            ERDFromScriptError::ParsingError(e) => Self::ParsingError(e),
+            ERDFromScriptError::ERDError(v) => Self::ERDError(v),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum PhysicalError {
-    DuplicateTableName(Ident),
-    DuplicateColumnNameInTable(Ident, Ident), // Column, Table
-    UnknownEntityOrRelationInTable(Ident, Ident), // EntityRelation, Table
-    ConvertedMoreThanOnce(Ident),             // Relation / Entity
-    ForgottenEntityOrRelation(Ident),
-    UnsupportedRelationDegree(Ident),
-    ForeignKeyToEntityInTable(Ident, Ident), // Entity, Table
-    ImpossibleForeignKey(Ident, Ident),      // Entity, Table
-    MissingDataTypeFromAttributeInEntityOrRelation(Ident, Ident), // Entity/Relation, Attribute
+    DuplicateTableName(Ident, Span),
+    DuplicateColumnNameInTable(Ident, Ident, Span), // Column, Table
+    UnknownEntityOrRelationInTable(Ident, Ident, Option<Ident>, Span), // EntityRelation, Table, Suggestion
+    ConvertedMoreThanOnce(Ident, Span),                                // Relation / Entity
+    ForgottenEntityOrRelation(Ident, Span),
+    ForeignKeyToEntityInTable(Ident, Ident, Span), // Entity, Table
+    ImpossibleForeignKey(Ident, Ident, Span),      // Entity, Table
+    MissingDataTypeFromAttributeInEntityOrRelation(Ident, Ident, Span), // Entity/Relation, Attribute
+    DefinitionInPhysicalMapping(Ident, Span),                           // Entity/Relation
+    SetNullOnRequiredForeignKey(Ident, Ident, Span),                    // Relation, Table
+    EnumWithoutValues(Ident, Ident, Span), // Entity/Relation, Attribute
+    WrongAmountOfForeignKeyColumns(Ident, Ident, Vec<Ident>, Span), // Relation, Table, Key
 }
 
-impl std::fmt::Display for PhysicalError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl PhysicalError {
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::DuplicateTableName(_, s)
+            | Self::DuplicateColumnNameInTable(_, _, s)
+            | Self::UnknownEntityOrRelationInTable(_, _, _, s)
+            | Self::ConvertedMoreThanOnce(_, s)
+            | Self::ForgottenEntityOrRelation(_, s)
+            | Self::ForeignKeyToEntityInTable(_, _, s)
+            | Self::ImpossibleForeignKey(_, _, s)
+            | Self::MissingDataTypeFromAttributeInEntityOrRelation(_, _, s)
+            | Self::DefinitionInPhysicalMapping(_, s)
+            | Self::SetNullOnRequiredForeignKey(_, _, s)
+            | Self::EnumWithoutValues(_, _, s)
+            | Self::WrongAmountOfForeignKeyColumns(_, _, _, s) => s,
+        }
+    }
+
+    /// The error message without its location
+    pub fn message(&self) -> String {
         match self {
-            Self::DuplicateTableName(i) => write!(f, "Table name {} is used multiple times.", i),
-            Self::DuplicateColumnNameInTable(i, e) => {
-                write!(f, "Multiple columns named {} in table {}.", i, e)
+            Self::DuplicateTableName(i, _) => format!("Table name {} is used multiple times.", i),
+            Self::DuplicateColumnNameInTable(i, e, _) => {
+                format!("Multiple columns named {} in table {}.", i, e)
             }
-            Self::UnknownEntityOrRelationInTable(e, r) => {
-                write!(f, "Unknown entity or relation {} in table {}.", e, r)
+            Self::UnknownEntityOrRelationInTable(e, r, _, _) => {
+                format!("Unknown entity or relation {} in table {}.", e, r)
             }
-            Self::ConvertedMoreThanOnce(e) => {
-                write!(
-                    f,
+            Self::ConvertedMoreThanOnce(e, _) => {
+                format!(
                     "Relation or entity {} is converted more than once to a table.",
                     e
                 )
             }
-            Self::ForgottenEntityOrRelation(e) => {
-                write!(
-                    f,
+            Self::ForgottenEntityOrRelation(e, _) => {
+                format!(
                     "Relation or entity {} is not converted to the physical representation.",
                     e
                 )
             }
-            Self::UnsupportedRelationDegree(e) => {
-                write!(
-                    f,
-                    "Relation {} has a degree different from 2 and that is (currently) not supported. Please convert the relation to relations of degree 2.",
-                    e
-                )
-            }
-            Self::ImpossibleForeignKey(e, t) => {
-                write!(
-                    f,
+            Self::ImpossibleForeignKey(e, t, _) => {
+                format!(
                     "It is impossible to create table {} with foreign key {}.",
                     t, e
                 )
             }
-            Self::ForeignKeyToEntityInTable(e, t) => {
-                write!(
-                    f,
+            Self::ForeignKeyToEntityInTable(e, t, _) => {
+                format!(
                     "You created a foreign key to the entity {} in table {} instead of to a relation.",
                     e, t
                 )
             }
-            Self::MissingDataTypeFromAttributeInEntityOrRelation(er, a) => {
-                write!(
-                    f,
+            Self::MissingDataTypeFromAttributeInEntityOrRelation(er, a, _) => {
+                format!(
                     "Attribute {} of relation or entity {} has no datatype specified.",
                     a, er
                 )
             }
+            Self::DefinitionInPhysicalMapping(er, _) => {
+                format!(
+                    "Relation or entity {} is defined in a physical mapping instead of in the ERD.",
+                    er
+                )
+            }
+            Self::SetNullOnRequiredForeignKey(r, t, _) => {
+                format!(
+                    "The foreign key for relation {} in table {} is required and can't be set to null.",
+                    r, t
+                )
+            }
+            Self::EnumWithoutValues(er, a, _) => {
+                format!(
+                    "The enum of attribute {} of relation or entity {} has no values.",
+                    a, er
+                )
+            }
+            Self::WrongAmountOfForeignKeyColumns(r, t, key, _) => {
+                format!(
+                    "The foreign key for relation {} in table {} needs {} column names.",
+                    r,
+                    t,
+                    key.len()
+                )
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PhysicalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.span().is_empty() {
+            write!(f, "{}", self.message())
+        } else {
+            write!(f, "{}: {}", self.span(), self.message())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sql::{Db2, Oracle, PostgreSQL, SQLite, MSSQL};
+
+    const ERD_SCRIPT: &str = "entity Person
+  id id type autoincrement
+
+entity Car
+  id id type autoincrement
+
+relation Ownership
+  one optional Person
+  multiple optional Car";
+
+    #[test]
+    fn map_erd_in_multiple_ways() {
+        let with_foreign_key = PhysicalDescription::from_scripts(
+            ERD_SCRIPT,
+            "table person from entity Person\n\ntable car from entity Car\nforeign key owner for Ownership",
+        )
+        .unwrap();
+        let with_table = PhysicalDescription::from_scripts(
+            ERD_SCRIPT,
+            "table person from entity Person\n\ntable car from entity Car\n\ntable ownership from relation Ownership",
+        )
+        .unwrap();
+        assert_eq!(with_foreign_key.to_physical().tables.len(), 2);
+        assert_eq!(with_table.to_physical().tables.len(), 3);
+    }
+
+    #[test]
+    fn sqlite_declares_keys_in_the_table() {
+        let physical = PhysicalDescription::from_scripts(
+            ERD_SCRIPT,
+            "table person from entity Person\n\ntable car from entity Car\n\ntable ownership from relation Ownership",
+        )
+        .unwrap();
+        let mut sql = String::new();
+        physical.to_physical().write_sql_create(&mut sql, &SQLite);
+        assert!(sql.contains("CREATE TABLE person (\nid INTEGER PRIMARY KEY AUTOINCREMENT\n);"));
+        assert!(sql.contains("FOREIGN KEY (Car_id) REFERENCES car(id)\n);"));
+        assert!(!sql.contains("ALTER TABLE"));
+    }
+
+    #[test]
+    fn write_column_modifiers() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity Person\n  id id type integer\n  attribute email type text required unique\n  attribute alive type boolean default true\n\nentity Car\n  id id type integer\n\nrelation Ownership\n  one required Person\n  multiple optional Car",
+            "table person from entity Person\n\ntable car from entity Car\nforeign key owner for Ownership",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &MSSQL);
+        assert!(sql.contains("email NVARCHAR(max) NOT NULL UNIQUE,\nalive BOOLEAN DEFAULT 1,"));
+        assert!(sql.contains("owner INTEGER NOT NULL,"));
+    }
+
+    #[test]
+    fn unary_relations_dont_depend_on_member_order() {
+        let erd = "entity Person\n  id id type integer\n\nrelation Mother\n  multiple optional Person as child\n  one required Person as mother\n\nrelation Friends\n  multiple optional Person as person\n  multiple optional Person as friend";
+        let physical = PhysicalDescription::from_scripts(
+            erd,
+            "table person from entity Person\nforeign key mother for Mother\n\ntable friendship from relation Friends",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.contains("mother INTEGER NOT NULL,"));
+        assert!(sql.contains("ALTER TABLE person ADD FOREIGN KEY (mother) REFERENCES person(id);"));
+        assert!(sql.contains(
+            "CREATE TABLE friendship (\nperson_id INTEGER,\nfriend_id INTEGER,\nPRIMARY KEY (person_id,friend_id)\n);"
+        ));
+
+        let reversed = erd.replace(
+            "  multiple optional Person as child\n  one required Person as mother",
+            "  one required Person as mother\n  multiple optional Person as child",
+        );
+        let physical = PhysicalDescription::from_scripts(
+            &reversed,
+            "table person from entity Person\nforeign key mother for Mother\n\ntable friendship from relation Friends",
+        )
+        .unwrap()
+        .to_physical();
+        let mut reversed_sql = String::new();
+        physical.write_sql_create(&mut reversed_sql, &PostgreSQL);
+        assert_eq!(sql, reversed_sql);
+    }
+
+    #[test]
+    fn map_ternary_relation_to_table() {
+        let erd = "entity Student\n  id id type integer\n\nentity Course\n  id id type integer\n\nentity Teacher\n  id id type integer\n\nrelation Teaches\n  multiple optional Student\n  multiple optional Course\n  one optional Teacher";
+        let physical = PhysicalDescription::from_scripts(
+            erd,
+            "table student from entity Student\n\ntable course from entity Course\n\ntable teacher from entity Teacher\n\ntable teaches from relation Teaches",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.contains(
+            "CREATE TABLE teaches (\nStudent_id INTEGER,\nCourse_id INTEGER,\nTeacher_id INTEGER NOT NULL,\nPRIMARY KEY (Student_id,Course_id)\n);"
+        ));
+        assert_eq!(
+            sql.matches("ALTER TABLE teaches ADD FOREIGN KEY").count(),
+            3
+        );
+
+        let errors = PhysicalDescription::from_scripts(
+            erd,
+            "table student from entity Student\n\ntable course from entity Course\n\ntable teacher from entity Teacher\nforeign key student for Teaches",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            errors,
+            PhysicalFromScriptError::PhysicalError(e)
+                if matches!(e.first(), Some(PhysicalError::ImpossibleForeignKey(..)))
+        ));
+    }
+
+    #[test]
+    fn leave_one_member_with_cardinality_one_out_of_the_key() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity Student\n  id id type integer\n\nentity Course\n  id id type integer\n\nentity Teacher\n  id id type integer\n\nrelation Follows\n  multiple optional Student\n  one optional Course\n  one optional Teacher",
+            "table student from entity Student\n\ntable course from entity Course\n\ntable teacher from entity Teacher\n\ntable follows from relation Follows",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.contains(
+            "CREATE TABLE follows (\nStudent_id INTEGER,\nCourse_id INTEGER,\nTeacher_id INTEGER NOT NULL,\nPRIMARY KEY (Student_id,Course_id)\n);"
+        ));
+        assert!(sql.contains(
+            "-- Relation Follows: (0,1) Course per Student and Teacher is not enforced.\n"
+        ));
+        assert_eq!(sql.matches("--").count(), 1);
+    }
+
+    #[test]
+    fn note_bounds_that_are_not_enforced() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity Team\n  id id type integer\n\nentity Player\n  id id type integer\n\nrelation Plays\n  between(2, 11) required Player\n  one optional Team\n\nrelation Captain\n  between(0, 1) optional Player\n  one optional Team",
+            "table team from entity Team\nforeign key captain for Captain\n\ntable player from entity Player\nforeign key team for Plays",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.starts_with("CREATE TABLE team (\nid INTEGER,\ncaptain INTEGER UNIQUE,"));
+        assert!(sql.contains(
+            "-- Relation Plays: (2,11) Player per Team is not enforced.\nCREATE TABLE player ("
+        ));
+        assert_eq!(sql.matches("--").count(), 1);
+    }
+
+    #[test]
+    fn write_referential_actions() {
+        let physical = PhysicalDescription::from_scripts(
+            ERD_SCRIPT,
+            "table person from entity Person\n\ntable car from entity Car\nforeign key owner for Ownership on delete restrict on update cascade",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.contains(
+            "ALTER TABLE car ADD FOREIGN KEY (owner) REFERENCES person(id) ON DELETE RESTRICT ON UPDATE CASCADE;"
+        ));
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &MSSQL);
+        assert!(sql.contains("ON DELETE NO ACTION ON UPDATE CASCADE;"));
+
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &Oracle);
+        assert!(sql.contains("REFERENCES person(id);"));
+        assert_eq!(
+            physical.check_foreign_keys(&Oracle),
+            vec!["On update cascade of the foreign key to person in table car is not supported, it is left out."]
+        );
+        assert!(physical.check_foreign_keys(&PostgreSQL).is_empty());
+    }
+
+    #[test]
+    fn required_foreign_key_cant_be_set_to_null() {
+        let errors = PhysicalDescription::from_scripts(
+            &ERD_SCRIPT.replace("one optional", "one required"),
+            "table person from entity Person\n\ntable car from entity Car\nforeign key owner for Ownership on delete set null",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            errors,
+            PhysicalFromScriptError::PhysicalError(e)
+                if matches!(&e[..], [PhysicalError::SetNullOnRequiredForeignKey(..)])
+        ));
+    }
+
+    #[test]
+    fn quote_reserved_words() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity Order\n  id id type integer\n  attribute user type text\n  attribute total type float",
+            "table order from entity Order",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert_eq!(
+            sql,
+            "CREATE TABLE \"order\" (\nid INTEGER,\n\"user\" TEXT,\ntotal FLOAT,\nPRIMARY KEY (id)\n);\n"
+        );
+        assert_eq!(
+            physical.check_identifiers(&PostgreSQL),
+            vec![
+                "The table name order is a reserved word.",
+                "The column name user of table order is a reserved word."
+            ]
+        );
+    }
+
+    #[test]
+    fn create_enum_types_once() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity Car\n  id id type integer\n  attribute brand type enum(audi,bmw)\n\nentity Bike\n  id id type integer\n  attribute brand type enum(audi,bmw)",
+            "table car from entity Car\n\ntable bike from entity Bike",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.starts_with(
+            "CREATE TYPE car_brand AS ENUM ('audi','bmw');\nCREATE TABLE car (\nid INTEGER,\nbrand car_brand,"
+        ));
+        assert_eq!(sql.matches("CREATE TYPE").count(), 1);
+        assert!(sql.contains("CREATE TABLE bike (\nid INTEGER,\nbrand car_brand,"));
+
+        // Nothing is remembered between compilations
+        let mut again = String::new();
+        physical.write_sql_create(&mut again, &PostgreSQL);
+        assert_eq!(sql, again);
+
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &MSSQL);
+        assert!(sql.contains("brand VARCHAR(4) CHECK (brand IN ('audi','bmw')),"));
+    }
+
+    #[test]
+    fn enum_type_names_dont_collide() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity A\n  id id type integer\n  attribute b_c type enum(x)\n\nentity AB\n  id id type integer\n  attribute c type enum(y)\n\nentity ABC\n  id id type integer",
+            "table a_b from entity AB\n\ntable a from entity A\n\ntable a_b_c from entity ABC",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.starts_with(
+            "CREATE TYPE a_b_c_2 AS ENUM ('y');\nCREATE TYPE a_b_c_3 AS ENUM ('x');\n"
+        ));
+    }
+
+    const DIALECT_ERD_SCRIPT: &str = "entity Account
+  id id type autoincrement
+  attribute active type boolean default true
+  attribute level type enum(gold,silver)
+  attribute opens type time
+  attribute key type uuid
+
+entity Transfer
+  id id type integer
+
+relation Makes
+  one required Account
+  multiple optional Transfer";
+
+    const DIALECT_PHYSICAL_SCRIPT: &str = "table account from entity Account
+
+table transfer from entity Transfer
+foreign key rowid for Makes on delete cascade on update cascade";
+
+    #[test]
+    fn write_oracle_sql() {
+        let physical =
+            PhysicalDescription::from_scripts(DIALECT_ERD_SCRIPT, DIALECT_PHYSICAL_SCRIPT)
+                .unwrap()
+                .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &Oracle);
+        assert_eq!(
+            sql,
+            "CREATE TABLE account (\nid NUMBER(10) GENERATED BY DEFAULT AS IDENTITY,\nactive NUMBER(1) DEFAULT 1 CHECK (active IN (0,1)),\n\"level\" VARCHAR2(6) CHECK (\"level\" IN ('gold','silver')),\nopens INTERVAL DAY(0) TO SECOND(0),\nkey RAW(16),\nPRIMARY KEY (id)\n);\nCREATE TABLE transfer (\nid NUMBER(10),\n\"rowid\" NUMBER(10) NOT NULL,\nPRIMARY KEY (id)\n);\nALTER TABLE transfer ADD FOREIGN KEY (\"rowid\") REFERENCES account(id) ON DELETE CASCADE;\n"
+        );
+        assert_eq!(
+            physical.check_identifiers(&Oracle),
+            vec![
+                "The column name level of table account is a reserved word.",
+                "The column name rowid of table transfer is a reserved word."
+            ]
+        );
+        assert_eq!(
+            physical.check_foreign_keys(&Oracle),
+            vec!["On update cascade of the foreign key to account in table transfer is not supported, it is left out."]
+        );
+    }
+
+    #[test]
+    fn write_db2_sql() {
+        let physical =
+            PhysicalDescription::from_scripts(DIALECT_ERD_SCRIPT, DIALECT_PHYSICAL_SCRIPT)
+                .unwrap()
+                .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &Db2);
+        assert_eq!(
+            sql,
+            "CREATE TABLE account (\nid INTEGER GENERATED BY DEFAULT AS IDENTITY NOT NULL,\nactive BOOLEAN DEFAULT TRUE,\nlevel VARCHAR(6) CHECK (level IN ('gold','silver')),\nopens TIME,\n\"key\" CHAR(16) FOR BIT DATA,\nPRIMARY KEY (id)\n);\nCREATE TABLE transfer (\nid INTEGER NOT NULL,\nrowid INTEGER NOT NULL,\nPRIMARY KEY (id)\n);\nALTER TABLE transfer ADD FOREIGN KEY (rowid) REFERENCES account(id) ON DELETE CASCADE;\n"
+        );
+        assert_eq!(
+            physical.check_identifiers(&Db2),
+            vec!["The column name key of table account is a reserved word."]
+        );
+        assert_eq!(
+            physical.check_foreign_keys(&Db2),
+            vec!["On update cascade of the foreign key to account in table transfer is not supported, it is left out."]
+        );
+    }
+
+    #[test]
+    fn enums_need_values() {
+        let attribute = Attribute {
+            ident: "level".to_string().into(),
+            r#type: AttributeType::Normal,
+            datatype: Some(DataType::Enum(Vec::new())),
+            modifiers: Vec::new(),
+            span: Span::default(),
+        };
+        let expressions = vec![
+            Expr::Entity(
+                "Account".to_string().into(),
+                vec![attribute],
+                false,
+                Span::default(),
+            ),
+            Expr::EntityTable(
+                "account".to_string().into(),
+                "Account".to_string().into(),
+                Vec::new(),
+                Span::default(),
+            ),
+        ];
+        let errors: Result<PhysicalDescription, PhysicalERDError> = expressions.try_into();
+        assert!(matches!(
+            errors,
+            Err(PhysicalERDError::Physical(e))
+                if matches!(&e[..], [PhysicalError::EnumWithoutValues(..)])
+        ));
+        assert_eq!(
+            Oracle.to_data_type(&DataType::Enum(Vec::new())),
+            "VARCHAR2(1)"
+        );
+    }
+
+    struct Batches;
+
+    impl SqlDialect for Batches {
+        fn to_data_type(&self, _data_type: &DataType) -> String {
+            "INT".to_string()
+        }
+
+        fn statement_terminator(&self) -> &str {
+            "\nGO"
+        }
+    }
+
+    #[test]
+    fn write_sql_for_a_custom_dialect() {
+        let physical = PhysicalDescription::from_scripts(
+            ERD_SCRIPT,
+            "table person from entity Person\n\ntable car from entity Car\nforeign key owner for Ownership",
+        )
+        .unwrap();
+        let mut sql = String::new();
+        physical.to_physical().write_sql_create(&mut sql, &Batches);
+        assert_eq!(
+            sql,
+            "CREATE TABLE person (\nid INT,\nPRIMARY KEY (id)\n)\nGO\nCREATE TABLE car (\nid INT,\nowner INT,\nPRIMARY KEY (id)\n)\nGO\nALTER TABLE car ADD FOREIGN KEY (owner) REFERENCES person(id)\nGO\n"
+        );
+    }
+
+    #[test]
+    fn definitions_belong_in_the_erd() {
+        let errors = match PhysicalDescription::from_scripts(
+            ERD_SCRIPT,
+            "entity Bike\n\ntable person from entity Person\n\ntable car from entity Car\n\ntable ownership from relation Ownership",
+        ) {
+            Err(PhysicalFromScriptError::PhysicalError(errors)) => errors,
+            other => panic!("Expected physical errors, got {:?}", other),
+        };
+        assert_eq!(
+            errors,
+            vec![PhysicalError::DefinitionInPhysicalMapping(
+                "Bike".to_string().into(),
+                Span {
+                    file: None,
+                    start: 7,
+                    end: 11,
+                    line: 1,
+                    column: 8
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn weak_entities_include_the_key_of_their_owner() {
+        let physical = PhysicalDescription::from_scripts(
+            "entity Building\n  id id type integer\n\nweak entity Room\n  id number type integer\n\nidentifying relation Contains\n  one required Building\n  multiple optional Room",
+            "table building from entity Building\n\ntable room from entity Room",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.contains(
+            "CREATE TABLE room (\nBuilding_id INTEGER,\nnumber INTEGER,\nPRIMARY KEY (Building_id,number)\n);"
+        ));
+        assert!(sql.contains(
+            "ALTER TABLE room ADD FOREIGN KEY (Building_id) REFERENCES building(id) ON DELETE CASCADE;"
+        ));
+
+        let errors = PhysicalDescription::from_scripts(
+            "entity Building\n  id id type integer\n\nweak entity Room\n  id number type integer\n\nidentifying relation Contains\n  one required Building\n  multiple optional Room",
+            "table building from entity Building\n\ntable room from entity Room\n\ntable contains from relation Contains",
+        );
+        assert!(matches!(
+            errors,
+            Err(PhysicalFromScriptError::PhysicalError(e))
+                if matches!(&e[..], [PhysicalError::ConvertedMoreThanOnce(..)])
+        ));
+    }
+
+    #[test]
+    fn foreign_keys_to_weak_entities_need_a_name_for_every_key_column() {
+        let erd = "entity Building\n  id code type integer\n\nweak entity Room\n  id number type integer\n\nidentifying relation Contains\n  one required Building\n  multiple optional Room\n\nentity Booking\n  id id type integer\n\nrelation BookedRoom\n  multiple optional Booking\n  one required Room";
+        let physical = PhysicalDescription::from_scripts(
+            erd,
+            "table building from entity Building\n\ntable room from entity Room\n\ntable booking from entity Booking\nforeign key room_building, room_number for BookedRoom",
+        )
+        .unwrap()
+        .to_physical();
+        let mut sql = String::new();
+        physical.write_sql_create(&mut sql, &PostgreSQL);
+        assert!(sql.contains(
+            "ALTER TABLE booking ADD FOREIGN KEY (room_building,room_number) REFERENCES room(Building_code,number);"
+        ));
+
+        let errors = PhysicalDescription::from_scripts(
+            erd,
+            "table building from entity Building\n\ntable room from entity Room\n\ntable booking from entity Booking\nforeign key room_id for BookedRoom",
+        );
+        match errors {
+            Err(PhysicalFromScriptError::PhysicalError(e)) => match &e[..] {
+                [PhysicalError::WrongAmountOfForeignKeyColumns(r, t, key, span)] => {
+                    assert_eq!(r, &Ident::from("BookedRoom".to_string()));
+                    assert_eq!(t, &Ident::from("booking".to_string()));
+                    assert_eq!(
+                        key,
+                        &vec![
+                            Ident::from("Building_code".to_string()),
+                            Ident::from("number".to_string())
+                        ]
+                    );
+                    assert!(!span.is_empty());
+                }
+                other => panic!("Expected a wrong amount of columns, got {:?}", other),
+            },
+            other => panic!("Expected physical errors, got {:?}", other),
         }
     }
 }
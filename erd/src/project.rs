@@ -0,0 +1,217 @@
+use crate::ast::{Expr, Span};
+use crate::diagnostic::Diagnostic;
+use crate::parser::ConsumeError;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// An erd-script file together with all files it (indirectly) imports
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Project {
+    files: Vec<SourceFile>,
+    expressions: Vec<Expr>,
+    errors: Vec<ImportError>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+}
+
+impl Project {
+    /// Loads `path` and resolves its imports relative to the importing file.
+    /// Files that can't be read or parsed are reported in `errors`, the expressions of all
+    /// well-formed blocks are merged in import order.
+    pub fn load(path: &Path) -> Self {
+        let mut project = Self::default();
+        project.load_file(path, None, &mut Vec::new(), &mut HashSet::new());
+        project
+    }
+
+    /// Like `load`, but for a source that isn't read from disk (e.g. stdin or an open editor),
+    /// its imports are resolved relative to the directory of `name`
+    pub fn from_source(name: &str, content: String) -> Self {
+        let mut project = Self::default();
+        let expressions = project.parse_source(name, content);
+        let directory = Path::new(name).parent().unwrap_or_else(|| Path::new(""));
+        let mut stack = Vec::new();
+        let mut loaded = HashSet::new();
+        if let Ok(canonical) = Path::new(name).canonicalize() {
+            loaded.insert(canonical.clone());
+            stack.push((canonical, name.to_string()));
+        }
+        project.resolve_imports(expressions, directory, &mut stack, &mut loaded);
+        project
+    }
+
+    /// Parses a single source without resolving its imports, they are kept as expressions
+    pub fn parse(name: &str, content: String) -> Self {
+        let mut project = Self::default();
+        project.expressions = project.parse_source(name, content);
+        project
+    }
+
+    fn load_file(
+        &mut self,
+        path: &Path,
+        import: Option<&Span>,
+        stack: &mut Vec<(PathBuf, String)>,
+        loaded: &mut HashSet<PathBuf>,
+    ) {
+        let display = path.to_string_lossy().to_string();
+        let read_error = |e: std::io::Error| {
+            ImportError::ReadError(display.clone(), e.to_string(), import.cloned())
+        };
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(e) => return self.errors.push(read_error(e)),
+        };
+        if let Some(idx) = stack.iter().position(|(p, _)| p == &canonical) {
+            let mut cycle: Vec<_> = stack[idx..].iter().map(|(_, d)| d.clone()).collect();
+            cycle.push(display);
+            return self.errors.push(ImportError::ImportCycle(
+                cycle,
+                import.cloned().unwrap_or_default(),
+            ));
+        }
+        if !loaded.insert(canonical.clone()) {
+            return;
+        }
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return self.errors.push(read_error(e)),
+        };
+
+        let expressions = self.parse_source(&display, content);
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        stack.push((canonical, display));
+        self.resolve_imports(expressions, directory, stack, loaded);
+        stack.pop();
+    }
+
+    fn parse_source(&mut self, name: &str, content: String) -> Vec<Expr> {
+        let expressions = match crate::parser::parse_as_erd(&content) {
+            Ok(pairs) => {
+                let (expressions, errors) =
+                    crate::parser::consume_expressions_recovering(pairs, Some(name));
+                self.errors
+                    .extend(errors.into_iter().map(ImportError::ParsingError));
+                expressions
+            }
+            Err(e) => {
+                self.errors
+                    .push(ImportError::ParsingError(ConsumeError::ERDParseError(
+                        vec![e.with_path(name)],
+                    )));
+                Vec::new()
+            }
+        };
+        self.files.push(SourceFile {
+            path: name.to_string(),
+            content,
+        });
+        expressions
+    }
+
+    fn resolve_imports(
+        &mut self,
+        expressions: Vec<Expr>,
+        directory: &Path,
+        stack: &mut Vec<(PathBuf, String)>,
+        loaded: &mut HashSet<PathBuf>,
+    ) {
+        for expr in expressions {
+            match expr {
+                Expr::Import(import_path, span) => {
+                    self.load_file(&directory.join(import_path), Some(&span), stack, loaded)
+                }
+                expr => self.expressions.push(expr),
+            }
+        }
+    }
+
+    /// The merged expressions of all files
+    pub fn expressions(&self) -> Vec<Expr> {
+        self.expressions.clone()
+    }
+
+    pub fn errors(&self) -> &[ImportError] {
+        &self.errors
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// The content of the file a span refers to
+    pub fn source(&self, file: Option<&str>) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|f| Some(&f.path[..]) == file)
+            .map(|f| &f.content[..])
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors
+            .iter()
+            .flat_map(|e| e.to_diagnostics())
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportError {
+    ReadError(String, String, Option<Span>), // Path, Reason, Import
+    ImportCycle(Vec<String>, Span),          // Files in the cycle, Import
+    ParsingError(ConsumeError),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadError(path, reason, _) => write!(f, "Could not read {}: {}.", path, reason),
+            Self::ImportCycle(files, _) => {
+                write!(f, "Import cycle: {}.", files.join(" -> "))
+            }
+            Self::ParsingError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_imports_and_detect_cycles() {
+        let dir = std::env::temp_dir().join(format!("erd-project-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(
+            dir.join("main.erd"),
+            "import \"sub/person.erd\"\nimport \"car.erd\"\n\nentity Main",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("sub/person.erd"),
+            "entity Person\n\nimport \"../car.erd\"",
+        )
+        .unwrap();
+        std::fs::write(dir.join("car.erd"), "entity Car\n\nimport \"main.erd\"").unwrap();
+
+        let project = Project::load(&dir.join("main.erd"));
+        let names: Vec<_> = project
+            .expressions()
+            .into_iter()
+            .filter_map(|e| match e {
+                Expr::Entity(name, _, _, _) => Some(name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["Person", "Car", "Main"]);
+        assert!(matches!(
+            project.errors(),
+            [ImportError::ImportCycle(files, _)] if files.len() == 4
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,933 @@
+use crate::ast::{
+    Attribute, AttributeType, DataType, Expr, ForeignKey, Ident, ReferentialAction,
+    RelationCardinality, RelationMember, RelationOptionality, Span,
+};
+use pest::iterators::Pair;
+use pest::Parser;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Parser)]
+#[grammar = "sql.pest"]
+struct SQLParser;
+
+/// The tables of an existing database
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    pub tables: Vec<SchemaTable>,
+    /// Named enum types, like the ones created by `CREATE TYPE ... AS ENUM`
+    pub enums: HashMap<String, Vec<String>>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaTable {
+    pub name: String,
+    pub columns: Vec<SchemaColumn>,
+    pub primary_key: Vec<String>,
+    pub foreign_keys: Vec<SchemaForeignKey>,
+    pub unique: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaColumn {
+    pub name: String,
+    /// The name of the datatype in upper case, e.g. `DOUBLE PRECISION`
+    pub type_name: Option<String>,
+    pub type_arguments: Vec<String>,
+    pub not_null: bool,
+    pub auto_increment: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaForeignKey {
+    pub columns: Vec<String>,
+    pub table: String,
+    pub referenced_columns: Vec<String>,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
+}
+
+impl Schema {
+    /// Reads the `CREATE TABLE`, `ALTER TABLE ... ADD` and `CREATE TYPE ... AS ENUM` statements
+    /// of a sql dump, other statements are skipped
+    #[allow(clippy::result_large_err)]
+    pub fn from_sql(sql: &str) -> Result<Self, pest::error::Error<Rule>> {
+        let mut schema = Self::default();
+        for statement in SQLParser::parse(Rule::sql, sql)? {
+            match statement.as_rule() {
+                Rule::create_table => schema.tables.push(consume_create_table(statement)),
+                Rule::alter_table => schema.consume_alter_table(statement),
+                Rule::create_type => {
+                    let mut inner = statement.into_inner();
+                    let name = consume_name(inner.next().unwrap());
+                    schema
+                        .enums
+                        .insert(name.to_lowercase(), inner.map(consume_string).collect());
+                }
+                Rule::other => {
+                    let words: Vec<_> = statement.as_str().split_whitespace().take(2).collect();
+                    if words.len() == 2
+                        && words[0].eq_ignore_ascii_case("create")
+                        && words[1].eq_ignore_ascii_case("table")
+                    {
+                        let (line, _) = statement.as_span().start_pos().line_col();
+                        schema.warnings.push(format!(
+                            "Could not read the CREATE TABLE statement on line {}, it is skipped.",
+                            line
+                        ));
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(schema)
+    }
+
+    fn consume_alter_table(&mut self, statement: Pair<Rule>) {
+        let mut inner = statement.into_inner();
+        let name = consume_name(inner.next().unwrap());
+        let mut table = SchemaTable::default();
+        consume_table_constraint(inner.next().unwrap(), &mut table);
+        match self
+            .tables
+            .iter_mut()
+            .find(|t| t.name.eq_ignore_ascii_case(&name))
+        {
+            Some(t) => {
+                t.foreign_keys.extend(table.foreign_keys);
+                t.unique.extend(table.unique);
+                if !table.primary_key.is_empty() {
+                    t.primary_key = table.primary_key;
+                }
+            }
+            None => self.warnings.push(format!(
+                "ALTER TABLE of unknown table {}, it is skipped.",
+                name
+            )),
+        }
+    }
+}
+
+fn consume_ident(pair: Pair<Rule>) -> String {
+    match pair.clone().into_inner().next() {
+        Some(inner) => consume_ident(inner),
+        None => pair.as_str().to_string(),
+    }
+}
+
+fn consume_name(pair: Pair<Rule>) -> String {
+    pair.into_inner()
+        .last()
+        .map(consume_ident)
+        .unwrap_or_default()
+}
+
+fn consume_ident_list(pair: Pair<Rule>) -> Vec<String> {
+    pair.into_inner()
+        .filter(|p| p.as_rule() == Rule::ident)
+        .map(consume_ident)
+        .collect()
+}
+
+fn consume_string(pair: Pair<Rule>) -> String {
+    let s = pair.as_str();
+    s[1..s.len() - 1].replace("''", "'")
+}
+
+/// Converts a sql referential action like `SET NULL`, actions without an erd equivalent are dropped
+fn to_referential_action(action: &str) -> Option<ReferentialAction> {
+    (action.to_lowercase(), Span::default()).try_into().ok()
+}
+
+fn consume_references(pair: Pair<Rule>, columns: Vec<String>) -> SchemaForeignKey {
+    let mut inner = pair.into_inner();
+    let mut foreign_key = SchemaForeignKey {
+        columns,
+        table: consume_name(inner.next().unwrap()),
+        ..Default::default()
+    };
+    for pair in inner {
+        match pair.as_rule() {
+            Rule::ident_list => foreign_key.referenced_columns = consume_ident_list(pair),
+            Rule::referential_action => {
+                let words: Vec<_> = pair.as_str().split_whitespace().collect();
+                let action = to_referential_action(&words[2..].join(" "));
+                if words[1].eq_ignore_ascii_case("delete") {
+                    foreign_key.on_delete = action;
+                } else {
+                    foreign_key.on_update = action;
+                }
+            }
+            _ => {}
+        }
+    }
+    foreign_key
+}
+
+fn consume_create_table(statement: Pair<Rule>) -> SchemaTable {
+    let mut inner = statement.into_inner();
+    let mut table = SchemaTable {
+        name: consume_name(inner.next().unwrap()),
+        ..Default::default()
+    };
+    for element in inner {
+        match element.as_rule() {
+            Rule::column => consume_column(element, &mut table),
+            Rule::table_constraint => consume_table_constraint(element, &mut table),
+            _ => (),
+        }
+    }
+    table
+}
+
+fn consume_column(pair: Pair<Rule>, table: &mut SchemaTable) {
+    let mut inner = pair.into_inner();
+    let mut column = SchemaColumn {
+        name: consume_ident(inner.next().unwrap()),
+        ..Default::default()
+    };
+    for option in inner {
+        match option.as_rule() {
+            Rule::data_type => {
+                for part in option.into_inner() {
+                    match part.as_rule() {
+                        Rule::type_name => {
+                            let words: Vec<_> = part.as_str().split_whitespace().collect();
+                            column.type_name = Some(words.join(" ").to_uppercase());
+                        }
+                        Rule::type_arguments => {
+                            column.type_arguments = part
+                                .into_inner()
+                                .map(|a| match a.clone().into_inner().next() {
+                                    Some(s) if s.as_rule() == Rule::string => consume_string(s),
+                                    _ => a.as_str().to_string(),
+                                })
+                                .collect()
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Rule::not_null => column.not_null = true,
+            Rule::column_primary_key => {
+                table.primary_key = vec![column.name.clone()];
+                column.auto_increment |= option.into_inner().next().is_some();
+            }
+            Rule::column_unique => table.unique.push(vec![column.name.clone()]),
+            Rule::references => table
+                .foreign_keys
+                .push(consume_references(option, vec![column.name.clone()])),
+            Rule::auto_increment | Rule::identity => column.auto_increment = true,
+            Rule::generated => {
+                column.auto_increment |= option.as_str().to_lowercase().contains("identity")
+            }
+            _ => (),
+        }
+    }
+    table.columns.push(column);
+}
+
+fn consume_table_constraint(pair: Pair<Rule>, table: &mut SchemaTable) {
+    for constraint in pair.into_inner() {
+        match constraint.as_rule() {
+            Rule::primary_key => {
+                table.primary_key = consume_ident_list(constraint.into_inner().next().unwrap())
+            }
+            Rule::foreign_key => {
+                let mut inner = constraint
+                    .into_inner()
+                    .skip_while(|p| p.as_rule() == Rule::ident);
+                let columns = consume_ident_list(inner.next().unwrap());
+                table
+                    .foreign_keys
+                    .push(consume_references(inner.next().unwrap(), columns));
+            }
+            Rule::unique_key => {
+                let list = constraint
+                    .into_inner()
+                    .find(|p| p.as_rule() == Rule::ident_list)
+                    .unwrap();
+                table.unique.push(consume_ident_list(list));
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Schema {
+    /// Reads the tables of a SQLite database file. The columns, foreign keys and unique
+    /// indexes are read with `PRAGMA`s, the `CREATE TABLE` statements in `sqlite_master` are
+    /// only used to find `AUTOINCREMENT` columns.
+    pub fn from_sqlite(path: &std::path::Path) -> Result<Self, rusqlite::Error> {
+        use rusqlite::{Connection, OpenFlags};
+
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let tables = connection
+            .prepare(
+                "SELECT name, sql FROM sqlite_master
+                 WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, Option<String>)>, _>>()?;
+
+        let mut schema = Self::default();
+        for (name, sql) in tables {
+            let auto_increment: HashSet<String> = Self::from_sql(sql.as_deref().unwrap_or(""))
+                .map(|parsed| {
+                    parsed
+                        .tables
+                        .into_iter()
+                        .flat_map(|t| t.columns)
+                        .filter(|c| c.auto_increment)
+                        .map(|c| c.name)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut table = SchemaTable {
+                name: name.clone(),
+                ..Default::default()
+            };
+
+            let mut primary_key = Vec::new();
+            let mut statement = connection.prepare(
+                "SELECT name, type, \"notnull\", pk FROM pragma_table_info(?1) ORDER BY cid",
+            )?;
+            let mut rows = statement.query([&name])?;
+            while let Some(row) = rows.next()? {
+                let column_name: String = row.get(0)?;
+                let (type_name, type_arguments) = split_type(&row.get::<_, String>(1)?);
+                let pk: usize = row.get(3)?;
+                if pk > 0 {
+                    primary_key.push((pk, column_name.clone()));
+                }
+                table.columns.push(SchemaColumn {
+                    auto_increment: auto_increment.contains(&column_name),
+                    name: column_name,
+                    type_name,
+                    type_arguments,
+                    not_null: row.get(2)?,
+                });
+            }
+            primary_key.sort();
+            table.primary_key = primary_key.into_iter().map(|(_, c)| c).collect();
+
+            // The foreign keys are listed in the opposite order of their definition
+            let mut statement = connection.prepare(
+                "SELECT id, \"table\", \"from\", \"to\", on_delete, on_update
+                 FROM pragma_foreign_key_list(?1)
+                 ORDER BY id DESC, seq",
+            )?;
+            let mut rows = statement.query([&name])?;
+            let mut last_id = None;
+            while let Some(row) = rows.next()? {
+                let id: usize = row.get(0)?;
+                if last_id != Some(id) {
+                    last_id = Some(id);
+                    table.foreign_keys.push(SchemaForeignKey {
+                        table: row.get(1)?,
+                        on_delete: to_referential_action(&row.get::<_, String>(4)?),
+                        on_update: to_referential_action(&row.get::<_, String>(5)?),
+                        ..Default::default()
+                    });
+                }
+                let foreign_key = table.foreign_keys.last_mut().unwrap();
+                foreign_key.columns.push(row.get(2)?);
+                if let Some(to) = row.get::<_, Option<String>>(3)? {
+                    foreign_key.referenced_columns.push(to);
+                }
+            }
+
+            let indexes = connection
+                .prepare(
+                    "SELECT name FROM pragma_index_list(?1)
+                     WHERE \"unique\" AND origin != 'pk' ORDER BY seq",
+                )?
+                .query_map([&name], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            for index in indexes {
+                table.unique.push(
+                    connection
+                        .prepare("SELECT name FROM pragma_index_info(?1) ORDER BY seqno")?
+                        .query_map([&index], |row| row.get(0))?
+                        .collect::<Result<Vec<String>, _>>()?,
+                );
+            }
+            schema.tables.push(table);
+        }
+        Ok(schema)
+    }
+}
+
+/// Splits a declared type like `DECIMAL(10, 2)` in its name and arguments
+#[cfg(feature = "sqlite")]
+fn split_type(declared: &str) -> (Option<String>, Vec<String>) {
+    let (name, arguments) = declared.split_once('(').unwrap_or((declared, ""));
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let arguments = arguments
+        .trim_end()
+        .trim_end_matches(')')
+        .split(',')
+        .map(|a| a.trim().trim_matches('\'').to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    ((!name.is_empty()).then(|| name.to_uppercase()), arguments)
+}
+
+impl SchemaTable {
+    fn column(&self, name: &str) -> Option<&SchemaColumn> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    fn foreign_key_columns(&self) -> HashSet<&str> {
+        self.foreign_keys
+            .iter()
+            .flat_map(|f| f.columns.iter().map(|c| &c[..]))
+            .collect()
+    }
+
+    /// A table that only links other tables, its primary key consists of foreign keys
+    fn is_junction(&self) -> bool {
+        let foreign_key_columns = self.foreign_key_columns();
+        self.foreign_keys.len() >= 2
+            && !self.primary_key.is_empty()
+            && self
+                .primary_key
+                .iter()
+                .all(|c| foreign_key_columns.contains(&c[..]))
+    }
+
+    fn is_unique(&self, columns: &[String]) -> bool {
+        let columns: HashSet<_> = columns.iter().collect();
+        std::iter::once(&self.primary_key)
+            .chain(self.unique.iter())
+            .any(|u| !u.is_empty() && u.iter().all(|c| columns.contains(c)))
+    }
+}
+
+impl SchemaColumn {
+    /// The erd-script datatype of the column, `None` if it is unknown
+    fn to_data_type(&self, enums: &HashMap<String, Vec<String>>) -> Option<DataType> {
+        let name = self.type_name.as_deref().unwrap_or("");
+        let numbers: Vec<Option<usize>> = self
+            .type_arguments
+            .iter()
+            .map(|a| a.parse().ok().filter(|n| *n > 0))
+            .collect();
+        let is_max = self
+            .type_arguments
+            .first()
+            .map_or(false, |a| a.eq_ignore_ascii_case("max"));
+        let data_type = match (name, &numbers[..]) {
+            ("SERIAL" | "BIGSERIAL" | "SMALLSERIAL" | "AUTOINCREMENT" | "COUNTER", _) => {
+                DataType::AutoIncrement
+            }
+            ("" | "INTEGER" | "INT" | "INT4" | "BIGINT" | "INT8" | "MEDIUMINT" | "LONG", _)
+            | ("NUMBER" | "DECIMAL" | "NUMERIC", [] | [_])
+                if self.auto_increment =>
+            {
+                DataType::AutoIncrement
+            }
+            ("INTEGER" | "INT" | "INT4" | "BIGINT" | "INT8" | "MEDIUMINT" | "LONG", _) => {
+                DataType::Integer
+            }
+            ("SMALLINT" | "TINYINT" | "INT2" | "SHORT" | "BYTE", _) => DataType::SmallInteger,
+            ("FLOAT" | "REAL" | "DOUBLE" | "DOUBLE PRECISION" | "FLOAT4" | "FLOAT8", _)
+            | ("BINARY_DOUBLE" | "BINARY_FLOAT", _) => DataType::Float,
+            ("BOOLEAN" | "BOOL" | "BIT" | "YESNO" | "LOGICAL", _) => DataType::Boolean,
+            ("DATE", _) => DataType::Date,
+            ("TIME" | "INTERVAL DAY", _) => DataType::Time,
+            ("DATETIME" | "DATETIME2" | "TIMESTAMP" | "TIMESTAMPTZ" | "SMALLDATETIME", _) => {
+                DataType::DateTime
+            }
+            (
+                "VARCHAR" | "NVARCHAR" | "VARCHAR2" | "NVARCHAR2" | "CHARACTER VARYING" | "CHAR"
+                | "NCHAR" | "CHARACTER",
+                [Some(n)],
+            ) => DataType::Varchar(*n),
+            ("VARBINARY" | "BINARY" | "VARBIT" | "BIT VARYING" | "RAW", [Some(n)]) => {
+                DataType::Varbinary(*n)
+            }
+            ("VARBINARY" | "BINARY", _) if is_max => DataType::Blob,
+            ("VARCHAR" | "NVARCHAR" | "CHARACTER VARYING" | "TEXT" | "NTEXT" | "LONGTEXT", _)
+            | ("MEDIUMTEXT" | "TINYTEXT" | "CLOB" | "NCLOB" | "LONGVARCHAR" | "MEMO", _) => {
+                DataType::Text
+            }
+            ("BLOB" | "LONGBLOB" | "MEDIUMBLOB" | "TINYBLOB" | "BYTEA" | "LONGBINARY", _)
+            | ("IMAGE" | "OLEOBJECT" | "VARBINARY", _) => DataType::Blob,
+            ("UUID" | "GUID" | "UNIQUEIDENTIFIER", _) => DataType::Uuid,
+            ("DECIMAL" | "NUMERIC" | "NUMBER", [Some(m), Some(d)]) => DataType::Decimal(*m, *d),
+            ("DECIMAL" | "NUMERIC" | "NUMBER", [_] | [_, _]) => DataType::Integer,
+            ("DECIMAL" | "NUMERIC" | "NUMBER" | "MONEY", _) => DataType::Float,
+            ("ENUM", _) => to_enum(&self.type_arguments)?,
+            (name, _) => to_enum(enums.get(&name.to_lowercase())?)?,
+        };
+        Some(data_type)
+    }
+}
+
+/// Enum values that can't be written in erd-script are not supported
+fn to_enum(values: &[String]) -> Option<DataType> {
+    let valid = |v: &String| !v.is_empty() && !v.contains(|c| matches!(c, ',' | ' ' | ')'));
+    (!values.is_empty() && values.iter().all(valid)).then(|| DataType::Enum(values.to_vec()))
+}
+
+/// An erd-script model and its tables reconstructed from a database schema
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReverseEngineered {
+    pub expressions: Vec<Expr>,
+    pub warnings: Vec<String>,
+}
+
+impl ReverseEngineered {
+    pub fn to_script(&self) -> String {
+        let mut s = self
+            .expressions
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        s.push('\n');
+        s
+    }
+}
+
+/// Gives out names that are valid erd-script identifiers and that aren't used yet
+#[derive(Default)]
+struct Names {
+    used: HashSet<String>,
+}
+
+impl Names {
+    fn unique(&mut self, name: &str) -> Ident {
+        let mut candidate = name.to_string();
+        let mut idx = 2;
+        while self.used.contains(&candidate.to_lowercase()) {
+            candidate = format!("{}{}", name, idx);
+            idx += 1;
+        }
+        self.used.insert(candidate.to_lowercase());
+        candidate.into()
+    }
+}
+
+/// Replaces the characters that can't be used in an identifier
+fn to_ident(name: &str) -> Ident {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident.insert(0, 't');
+    }
+    ident.into()
+}
+
+/// `car_owner` becomes `CarOwner`
+fn to_pascal_case(name: &str) -> String {
+    let pascal: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    to_ident(&pascal).0
+}
+
+/// The datatype of a column, unknown datatypes become `text`
+fn data_type(
+    schema: &Schema,
+    table: &SchemaTable,
+    column: &SchemaColumn,
+    warnings: &mut Vec<String>,
+) -> Option<DataType> {
+    let data_type = column.to_data_type(&schema.enums);
+    if data_type.is_none() {
+        warnings.push(format!(
+            "Unknown datatype {} of column {}.{}, text is used instead.",
+            column.type_name.as_deref().unwrap_or("(none)"),
+            table.name,
+            column.name
+        ));
+    }
+    Some(data_type.unwrap_or(DataType::Text))
+}
+
+fn attribute(name: &str, r#type: AttributeType, datatype: Option<DataType>) -> Attribute {
+    Attribute {
+        ident: to_ident(name),
+        r#type,
+        datatype,
+        modifiers: Vec::new(),
+        span: Span::default(),
+    }
+}
+
+fn member(
+    entity: &Ident,
+    cardinality: RelationCardinality,
+    optionality: RelationOptionality,
+) -> RelationMember {
+    RelationMember {
+        cardinality,
+        optionality,
+        entity: entity.clone(),
+        role: None,
+        span: Span::default(),
+    }
+}
+
+/// The role of a member that is referred to with `columns`, e.g. `mother` for `mother_id`
+fn role(columns: &[String]) -> Ident {
+    let column = columns[0].to_lowercase();
+    to_ident(
+        column
+            .strip_suffix("_id")
+            .filter(|c| !c.is_empty())
+            .unwrap_or(&column),
+    )
+}
+
+impl std::convert::From<&Schema> for ReverseEngineered {
+    fn from(schema: &Schema) -> Self {
+        let mut warnings = schema.warnings.clone();
+        let mut names = Names::default();
+        let entity_names: HashMap<&str, Ident> = schema
+            .tables
+            .iter()
+            .filter(|t| !t.is_junction())
+            .map(|t| (&t.name[..], names.unique(&to_pascal_case(&t.name))))
+            .collect();
+
+        let mut entities = Vec::new();
+        let mut relations = Vec::new();
+        let mut tables = Vec::new();
+        for table in schema.tables.iter() {
+            let table_name = to_ident(&table.name);
+            if table.is_junction() {
+                let relation = names.unique(&to_pascal_case(&table.name));
+                let mut members: Vec<_> = table
+                    .foreign_keys
+                    .iter()
+                    .filter_map(|f| {
+                        let in_primary_key =
+                            f.columns.iter().all(|c| table.primary_key.contains(c));
+                        let cardinality = if in_primary_key {
+                            RelationCardinality::Multiple
+                        } else {
+                            RelationCardinality::One
+                        };
+                        match entity_names.get(&f.table[..]) {
+                            Some(entity) => Some((
+                                member(entity, cardinality, RelationOptionality::Optional),
+                                role(&f.columns),
+                            )),
+                            None => {
+                                warnings.push(format!(
+                                    "Table {} references {}, which is no entity table.",
+                                    table.name, f.table
+                                ));
+                                None
+                            }
+                        }
+                    })
+                    .collect();
+                // Entities that occur multiple times need roles to tell the members apart
+                let entities: Vec<_> = members.iter().map(|(m, _)| m.entity.clone()).collect();
+                for (member, role) in members.iter_mut() {
+                    if entities.iter().filter(|e| **e == member.entity).count() > 1 {
+                        member.role = Some(role.clone());
+                    }
+                }
+                let members = members.into_iter().map(|(m, _)| m).collect();
+                let foreign_key_columns = table.foreign_key_columns();
+                let attributes = table
+                    .columns
+                    .iter()
+                    .filter(|c| !foreign_key_columns.contains(&c.name[..]))
+                    .map(|c| {
+                        attribute(
+                            &c.name,
+                            AttributeType::Normal,
+                            data_type(schema, table, c, &mut warnings),
+                        )
+                    })
+                    .collect();
+                relations.push(Expr::Relation(
+                    relation.clone(),
+                    None,
+                    members,
+                    attributes,
+                    false,
+                    Span::default(),
+                ));
+                tables.push(Expr::RelationTable(table_name, relation, Span::default()));
+                continue;
+            }
+
+            let entity = entity_names[&table.name[..]].clone();
+            let mut foreign_keys = Vec::new();
+            let mut relation_columns: HashSet<&str> = HashSet::new();
+            for f in table.foreign_keys.iter() {
+                if f.columns.iter().any(|c| table.primary_key.contains(c)) {
+                    warnings.push(format!(
+                        "The foreign key ({}) of table {} is part of its primary key, it is kept as an identifier.",
+                        f.columns.join(", "),
+                        table.name
+                    ));
+                    continue;
+                }
+                let other = match entity_names.get(&f.table[..]) {
+                    Some(other) => other,
+                    None => {
+                        warnings.push(format!(
+                            "The foreign key ({}) of table {} references {}, which is no entity table, it is kept as attributes.",
+                            f.columns.join(", "),
+                            table.name,
+                            f.table
+                        ));
+                        continue;
+                    }
+                };
+                let relation_name = match &f.columns[..] {
+                    [column] => {
+                        let column = column.to_lowercase();
+                        let column = column
+                            .strip_suffix("_id")
+                            .filter(|c| !c.is_empty())
+                            .unwrap_or(&column);
+                        format!("{}{}", entity, to_pascal_case(column))
+                    }
+                    _ => format!("{}{}", entity, other),
+                };
+                let relation = names.unique(&relation_name);
+                let required = f
+                    .columns
+                    .iter()
+                    .all(|c| table.column(c).map_or(false, |c| c.not_null));
+                let own_cardinality = if table.is_unique(&f.columns) {
+                    RelationCardinality::One
+                } else {
+                    RelationCardinality::Multiple
+                };
+                let mut referenced = member(
+                    other,
+                    RelationCardinality::One,
+                    if required {
+                        RelationOptionality::Required
+                    } else {
+                        RelationOptionality::Optional
+                    },
+                );
+                let mut referencing =
+                    member(&entity, own_cardinality, RelationOptionality::Optional);
+                if other == &entity {
+                    let referenced_role = role(&f.columns);
+                    let mut referencing_role = to_ident(&table.name.to_lowercase());
+                    if referencing_role == referenced_role {
+                        referencing_role = format!("{}2", referencing_role).into();
+                    }
+                    referenced.role = Some(referenced_role);
+                    referencing.role = Some(referencing_role);
+                }
+                relations.push(Expr::Relation(
+                    relation.clone(),
+                    None,
+                    vec![referenced, referencing],
+                    Vec::new(),
+                    false,
+                    Span::default(),
+                ));
+                foreign_keys.push(ForeignKey {
+                    attribute_names: f.columns.iter().map(|c| to_ident(c)).collect(),
+                    relation,
+                    on_delete: f.on_delete.clone(),
+                    on_update: f.on_update.clone(),
+                    span: Span::default(),
+                });
+                relation_columns.extend(f.columns.iter().map(|c| &c[..]));
+            }
+
+            let attributes = table
+                .columns
+                .iter()
+                .filter(|c| !relation_columns.contains(&c.name[..]))
+                .map(|c| {
+                    let r#type = if table.primary_key.contains(&c.name) {
+                        AttributeType::Key
+                    } else {
+                        AttributeType::Normal
+                    };
+                    attribute(&c.name, r#type, data_type(schema, table, c, &mut warnings))
+                })
+                .collect();
+            entities.push(Expr::Entity(
+                entity.clone(),
+                attributes,
+                false,
+                Span::default(),
+            ));
+            tables.push(Expr::EntityTable(
+                table_name,
+                entity,
+                foreign_keys,
+                Span::default(),
+            ));
+        }
+
+        Self {
+            expressions: entities
+                .into_iter()
+                .chain(relations)
+                .chain(tables)
+                .collect(),
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::physical::PhysicalDescription;
+    use crate::sql::SQL;
+    use std::convert::TryInto;
+
+    #[test]
+    fn reverse_engineer_mysql() {
+        let sql = "-- A dump
+CREATE TABLE `person` (
+  `id` INT(11) NOT NULL AUTO_INCREMENT,
+  `name` VARCHAR(100) NOT NULL,
+  `kind` ENUM('a','b') DEFAULT 'a',
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB;
+CREATE TABLE car (
+  plate VARCHAR(10) PRIMARY KEY,
+  price DECIMAL(10, 2),
+  owner_id INT NOT NULL REFERENCES person(id)
+);
+CREATE TABLE drives (
+  person_id INT,
+  car_plate VARCHAR(10),
+  since DATETIME,
+  PRIMARY KEY (person_id, car_plate),
+  CONSTRAINT fk_car FOREIGN KEY (car_plate) REFERENCES car (plate) ON DELETE CASCADE
+);
+ALTER TABLE drives ADD FOREIGN KEY (person_id) REFERENCES person(id);
+INSERT INTO person VALUES (1, 'a;b', 'a');
+CREATE INDEX car_price ON car (price);
+";
+        let schema = Schema::from_sql(sql).unwrap();
+        let reversed = ReverseEngineered::from(&schema);
+        assert_eq!(reversed.warnings, Vec::<String>::new());
+        assert_eq!(
+            reversed.to_script(),
+            "entity Person
+  id id type autoincrement
+  attribute name type varchar(100)
+  attribute kind type enum(a, b)
+
+entity Car
+  id plate type varchar(10)
+  attribute price type decimal(10, 2)
+
+relation CarOwner
+  one required Person
+  multiple optional Car
+
+relation Drives
+  multiple optional Car
+  multiple optional Person
+  attribute since type datetime
+
+table person from entity Person
+
+table car from entity Car
+foreign key owner_id for CarOwner
+
+table drives from relation Drives
+"
+        );
+        let physical: Result<PhysicalDescription, _> = reversed.expressions.try_into();
+        assert!(physical.is_ok());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn reverse_engineer_sqlite() {
+        let path = std::env::temp_dir().join(format!("erd-sqlite-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE person (id INTEGER PRIMARY KEY AUTOINCREMENT, email VARCHAR(50));
+                 CREATE UNIQUE INDEX person_email ON person (email);
+                 CREATE TABLE car (id INTEGER PRIMARY KEY, owner INTEGER NOT NULL,
+                   FOREIGN KEY (owner) REFERENCES person);
+                 CREATE TABLE follows (a INTEGER REFERENCES person(id), b INTEGER REFERENCES person(id),
+                   PRIMARY KEY (a, b));",
+            )
+            .unwrap();
+        drop(connection);
+
+        let schema = Schema::from_sqlite(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(schema.tables[0].unique, vec![vec!["email".to_string()]]);
+        assert_eq!(
+            schema.tables[2].foreign_keys[0],
+            SchemaForeignKey {
+                columns: vec!["a".to_string()],
+                table: "person".to_string(),
+                referenced_columns: vec!["id".to_string()],
+                ..Default::default()
+            }
+        );
+        let script = ReverseEngineered::from(&schema).to_script();
+        assert!(script.contains("entity Person\n  id id type autoincrement\n"));
+        assert!(script.contains("entity Car\n  id id type integer\n"));
+        assert!(
+            script.contains("relation CarOwner\n  one required Person\n  multiple optional Car")
+        );
+        assert!(script.contains(
+            "relation Follows\n  multiple optional Person as a\n  multiple optional Person as b"
+        ));
+        assert!(script.contains("table follows from relation Follows"));
+    }
+
+    #[test]
+    fn read_the_generated_sql() {
+        let physical = PhysicalDescription::from_scripts(
+            &std::fs::read_to_string("../examples/mapping/model.erd").unwrap(),
+            &std::fs::read_to_string("../examples/mapping/oltp.erd").unwrap(),
+        )
+        .unwrap();
+        for dialect in [
+            "ms_access",
+            "libre_office_base",
+            "mysql",
+            "postgresql",
+            "mssql",
+            "sqlite",
+            "oracle",
+            "db2",
+        ] {
+            let mut sql = String::new();
+            physical
+                .to_physical()
+                .write_sql_create(&mut sql, dialect.parse::<SQL>().unwrap().dialect());
+            let reversed = ReverseEngineered::from(&Schema::from_sql(&sql).unwrap());
+            assert_eq!(reversed.warnings, Vec::<String>::new(), "{}", dialect);
+            assert!(
+                reversed
+                    .to_script()
+                    .contains("table car from entity Car\nforeign key owner for CarOwner"),
+                "{}",
+                dialect
+            );
+        }
+    }
+}
@@ -1,5 +1,140 @@
-use crate::ast::{DataType, Ident};
+use crate::ast::{DataType, Ident, ReferentialAction};
 
+pub use self::db2::Db2;
+pub use self::libre_office_base::LibreOfficeBase;
+pub use self::ms_access::MSAccess;
+pub use self::mssql::MSSQL;
+pub use self::mysql::MySQL;
+pub use self::oracle::Oracle;
+pub use self::postgresql::PostgreSQL;
+pub use self::sqlite::SQLite;
+
+/// How a dbms stores the values of an enum
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EnumStrategy {
+    /// The datatype of the column lists the values, like `ENUM('a','b')`
+    Native,
+    /// A type is created for the values and used as the datatype of the columns
+    NamedType,
+    /// The column has a check that only allows the values
+    Check,
+}
+
+/// The methods needed for SQL creation. Implement it to generate sql for a dbms that is
+/// not part of [`SQL`].
+pub trait SqlDialect {
+    /// The datatype of a column
+    fn to_data_type(&self, data_type: &DataType) -> String;
+
+    /// The name of a table, column or type, quoted if it is reserved or contains
+    /// special characters
+    fn to_ident(&self, ident: &Ident) -> String {
+        let name = &ident.0;
+        let is_plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_plain && !self.is_reserved(name) {
+            name.to_string()
+        } else {
+            self.quote(name)
+        }
+    }
+
+    /// Quotes a name, which makes it possible to use reserved words
+    fn quote(&self, name: &str) -> String {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+
+    /// The words that are reserved by the dbms on top of the ones of the sql standard
+    fn reserved_words(&self) -> &[&str] {
+        &[]
+    }
+
+    fn is_reserved(&self, name: &str) -> bool {
+        RESERVED
+            .iter()
+            .chain(self.reserved_words().iter())
+            .any(|w| w.eq_ignore_ascii_case(name))
+    }
+
+    fn enum_strategy(&self) -> EnumStrategy {
+        EnumStrategy::Check
+    }
+
+    /// The statement that creates an enum type, for [`EnumStrategy::NamedType`]
+    fn to_enum_type(&self, name: &Ident, options: &[String]) -> String {
+        format!(
+            "CREATE TYPE {} AS ENUM ({}){}",
+            self.to_ident(name),
+            to_values(options),
+            self.statement_terminator()
+        )
+    }
+
+    /// A literal of an attribute modifier like `default true`
+    fn to_default_value(&self, value: &str) -> String {
+        match value {
+            "true" => "TRUE".to_string(),
+            "false" => "FALSE".to_string(),
+            _ => value.to_string(),
+        }
+    }
+
+    /// The check of an enum column, for [`EnumStrategy::Check`]
+    fn to_enum_check(&self, ident: &Ident, options: &[String]) -> String {
+        format!(
+            "CHECK ({} IN ({}))",
+            self.to_ident(ident),
+            to_values(options)
+        )
+    }
+
+    /// A constraint that is added to the definition of a column
+    fn to_column_constraint(&self, _ident: &Ident, _data_type: &DataType) -> Option<String> {
+        None
+    }
+
+    /// Whether the datatype itself makes the column the primary key of its table
+    fn declares_primary_key(&self, _data_type: &DataType) -> bool {
+        false
+    }
+
+    /// Whether the columns of the primary key have to be declared `NOT NULL`
+    fn requires_not_null_key(&self) -> bool {
+        false
+    }
+
+    /// Whether foreign keys are part of `CREATE TABLE` instead of being added with
+    /// `ALTER TABLE` afterwards
+    fn has_inline_foreign_keys(&self) -> bool {
+        false
+    }
+
+    /// The `ON DELETE` action of a foreign key, `None` leaves it out so the dbms restricts
+    fn to_on_delete(&self, action: &ReferentialAction) -> Option<String> {
+        Some(to_referential_action(action))
+    }
+
+    /// The `ON UPDATE` action of a foreign key, `None` leaves it out so the dbms restricts
+    fn to_on_update(&self, action: &ReferentialAction) -> Option<String> {
+        Some(to_referential_action(action))
+    }
+
+    /// The text that ends every statement
+    fn statement_terminator(&self) -> &str {
+        ";"
+    }
+}
+
+/// The action as written in standard sql
+pub fn to_referential_action(action: &ReferentialAction) -> String {
+    match action {
+        ReferentialAction::Cascade => "CASCADE".to_string(),
+        ReferentialAction::SetNull => "SET NULL".to_string(),
+        ReferentialAction::Restrict => "RESTRICT".to_string(),
+    }
+}
+
+/// The dialects that can be chosen by name
 #[derive(Debug, Clone, Copy)]
 pub enum SQL {
     MSAccess,
@@ -7,343 +142,863 @@ pub enum SQL {
     MySQL,
     PostgreSQL,
     MSSQL,
+    SQLite,
+    Oracle,
+    Db2,
 }
 
-impl SQL {
-    pub fn from_str(s: &str) -> Option<Self> {
+impl std::str::FromStr for SQL {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "ms_access" => Some(Self::MSAccess),
-            "libre_office_base" => Some(Self::LibreOfficeBase),
-            "mysql" => Some(Self::MySQL),
-            "postgresql" => Some(Self::PostgreSQL),
-            "mssql" => Some(Self::MSSQL),
-            _ => None,
+            "ms_access" => Ok(Self::MSAccess),
+            "libre_office_base" => Ok(Self::LibreOfficeBase),
+            "mysql" => Ok(Self::MySQL),
+            "postgresql" => Ok(Self::PostgreSQL),
+            "mssql" => Ok(Self::MSSQL),
+            "sqlite" => Ok(Self::SQLite),
+            "oracle" => Ok(Self::Oracle),
+            "db2" => Ok(Self::Db2),
+            _ => Err(format!("Unknown dbms {}.", s)),
         }
     }
 }
 
-// The methods needed for SQL creation
 impl SQL {
-    pub fn to_additional_definitions(&self, data_type: &DataType) -> Option<String> {
-        match self {
-            Self::MSAccess => ms_access::to_additional_definitions(data_type),
-            Self::LibreOfficeBase => libre_office_base::to_additional_definitions(data_type),
-            Self::MySQL => mysql::to_additional_definitions(data_type),
-            Self::PostgreSQL => postgresql::to_additional_definitions(data_type),
-            Self::MSSQL => mssql::to_additional_definitions(data_type),
-        }
-    }
-
-    pub fn to_data_type(&self, data_type: &DataType) -> String {
-        match self {
-            Self::MSAccess => ms_access::to_data_type(data_type),
-            Self::LibreOfficeBase => libre_office_base::to_data_type(data_type),
-            Self::MySQL => mysql::to_data_type(data_type),
-            Self::PostgreSQL => postgresql::to_data_type(data_type),
-            Self::MSSQL => mssql::to_data_type(data_type),
-        }
+    /// Kept for compatibility, `str::parse` also tells which name is unknown
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        s.parse().ok()
     }
 
-    pub fn to_column_ident(&self, ident: &Ident) -> String {
+    pub fn dialect(&self) -> &'static dyn SqlDialect {
         match self {
-            Self::MSAccess => ms_access::to_column_ident(ident),
-            Self::LibreOfficeBase => libre_office_base::to_column_ident(ident),
-            Self::MySQL => mysql::to_column_ident(ident),
-            Self::PostgreSQL => postgresql::to_column_ident(ident),
-            Self::MSSQL => mssql::to_column_ident(ident),
+            Self::MSAccess => &MSAccess,
+            Self::LibreOfficeBase => &LibreOfficeBase,
+            Self::MySQL => &MySQL,
+            Self::PostgreSQL => &PostgreSQL,
+            Self::MSSQL => &MSSQL,
+            Self::SQLite => &SQLite,
+            Self::Oracle => &Oracle,
+            Self::Db2 => &Db2,
         }
     }
 }
 
 mod ms_access {
-    use crate::ast::{DataType, Ident};
+    use crate::ast::{DataType, Ident, ReferentialAction};
+
+    use super::{to_referential_action, MaxLength, SqlDialect};
+
+    pub struct MSAccess;
 
-    use super::MaxLength;
-
-    pub fn to_additional_definitions(data_type: &DataType) -> Option<String> {
-        match data_type {
-            DataType::Enum(_) => None,
-            DataType::Integer
-            | DataType::SmallInteger
-            | DataType::AutoIncrement
-            | DataType::Float
-            | DataType::Boolean
-            | DataType::Date
-            | DataType::Time
-            | DataType::DateTime
-            | DataType::Varchar(_)
-            | DataType::Varbinary(_)
-            | DataType::Blob
-            | DataType::Text
-            | DataType::Uuid
-            | DataType::Decimal(_, _) => None,
+    impl SqlDialect for MSAccess {
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "INTEGER".to_string(), // TODO
+                DataType::AutoIncrement => "AUTOINCREMENT".to_string(),
+                DataType::Float => "FLOAT".to_string(),
+                DataType::Boolean => "YESNO".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "DATETIME".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR({})", n),
+
+                DataType::Varbinary(n) => format!("VARBINARY({n})"),
+                DataType::Blob => "LONGBINARY".to_string(),
+                DataType::Text => "TEXT".to_string(),
+                DataType::Uuid => "GUID".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                DataType::Enum(options) => format!("VARCHAR({})", options.max_length()),
+            }
         }
-    }
 
-    pub fn to_data_type(data_type: &DataType) -> String {
-        match data_type {
-            DataType::Integer => "INTEGER".to_string(),
-            DataType::SmallInteger => "INTEGER".to_string(), // TODO
-            DataType::AutoIncrement => "AUTOINCREMENT".to_string(),
-            DataType::Float => "FLOAT".to_string(),
-            DataType::Boolean => "YESNO".to_string(),
-            DataType::Date => "DATE".to_string(),
-            DataType::Time => "TIME".to_string(),
-            DataType::DateTime => "DATETIME".to_string(),
-            DataType::Varchar(n) => format!("VARCHAR({})", n),
-
-            DataType::Varbinary(n) => format!("VARBINARY({n})"),
-            DataType::Blob => "LONGBINARY".to_string(),
-            DataType::Text => "TEXT".to_string(),
-            DataType::Uuid => "GUID".to_string(),
-            DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
-            DataType::Enum(options) => format!("VARCHAR({})", options.max_length().unwrap()),
-            // TODO CHECK (mycol IN('a', 'b'))
+        fn to_ident(&self, ident: &Ident) -> String {
+            self.quote(&ident.0)
+        }
+
+        fn quote(&self, name: &str) -> String {
+            format!("[{}]", name)
+        }
+
+        // There is no RESTRICT, restricting is the default
+        fn to_on_delete(&self, action: &ReferentialAction) -> Option<String> {
+            match action {
+                ReferentialAction::Restrict => None,
+                _ => Some(to_referential_action(action)),
+            }
+        }
+
+        fn to_on_update(&self, action: &ReferentialAction) -> Option<String> {
+            self.to_on_delete(action)
+        }
+
+        // See https://support.microsoft.com/en-us/office/learn-about-access-reserved-words-and-symbols-ae9d9ada-3255-4b12-91a9-f855bdd9c5a2
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "ALPHANUMERIC",
+                "AUTOINCREMENT",
+                "BINARY",
+                "BIT",
+                "BOOLEAN",
+                "BYTE",
+                "COUNTER",
+                "CURRENCY",
+                "DATABASE",
+                "DATE",
+                "DATETIME",
+                "DISALLOW",
+                "DISTINCTROW",
+                "DOUBLE",
+                "FIELD",
+                "FLOAT",
+                "GUID",
+                "IGNORE",
+                "INDEX",
+                "INTEGER",
+                "KEY",
+                "LEVEL",
+                "LONG",
+                "LONGBINARY",
+                "LONGTEXT",
+                "MEMO",
+                "MONEY",
+                "NAME",
+                "NUMBER",
+                "OLEOBJECT",
+                "OPTION",
+                "OWNERACCESS",
+                "PARAMETERS",
+                "PERCENT",
+                "PIVOT",
+                "PROCEDURE",
+                "REAL",
+                "SHORT",
+                "SINGLE",
+                "SMALLINT",
+                "TEXT",
+                "TIME",
+                "TIMESTAMP",
+                "TOP",
+                "TRANSFORM",
+                "TYPE",
+                "USER",
+                "VALUE",
+                "VARBINARY",
+                "VARCHAR",
+                "YEAR",
+                "YESNO",
+            ]
         }
-    }
-    pub fn to_column_ident(ident: &Ident) -> String {
-        format!("[{}]", ident.to_string())
     }
 }
 
 mod libre_office_base {
-    use crate::ast::{DataType, Ident};
+    use crate::ast::{DataType, ReferentialAction};
+
+    use super::{to_referential_action, MaxLength, SqlDialect};
+
+    pub struct LibreOfficeBase;
 
-    use super::MaxLength;
-
-    pub fn to_additional_definitions(data_type: &DataType) -> Option<String> {
-        match data_type {
-            DataType::Enum(_) => None,
-            DataType::Integer
-            | DataType::SmallInteger
-            | DataType::AutoIncrement
-            | DataType::Float
-            | DataType::Boolean
-            | DataType::Date
-            | DataType::Time
-            | DataType::DateTime
-            | DataType::Varchar(_)
-            | DataType::Varbinary(_)
-            | DataType::Blob
-            | DataType::Text
-            | DataType::Uuid
-            | DataType::Decimal(_, _) => None,
+    impl SqlDialect for LibreOfficeBase {
+        // See http://www.hsqldb.org/doc/1.8/guide/guide.html#datatypes-section
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "INTEGER".to_string(), // TODO
+                DataType::AutoIncrement => "INTEGER GENERATED BY DEFAULT AS IDENTITY".to_string(),
+                DataType::Float => "FLOAT".to_string(),
+                DataType::Boolean => "BOOLEAN".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "DATETIME".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR({})", n),
+
+                DataType::Varbinary(n) => format!("VARBINARY({n})"),
+                DataType::Blob => "BLOB".to_string(),
+                DataType::Text => "LONGVARCHAR".to_string(),
+                DataType::Uuid => "UUID".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                DataType::Enum(options) => format!("VARCHAR({})", options.max_length()),
+            }
         }
-    }
 
-    // See http://www.hsqldb.org/doc/1.8/guide/guide.html#datatypes-section
-    pub fn to_data_type(data_type: &DataType) -> String {
-        match data_type {
-            DataType::Integer => "INTEGER".to_string(),
-            DataType::SmallInteger => "INTEGER".to_string(), // TODO
-            DataType::AutoIncrement => "INTEGER GENERATED BY DEFAULT AS IDENTITY".to_string(),
-            DataType::Float => "FLOAT".to_string(),
-            DataType::Boolean => "BOOLEAN".to_string(),
-            DataType::Date => "DATE".to_string(),
-            DataType::Time => "TIME".to_string(),
-            DataType::DateTime => "DATETIME".to_string(),
-            DataType::Varchar(n) => format!("VARCHAR({})", n),
-
-            DataType::Varbinary(n) => format!("VARBINARY({n})"),
-            DataType::Blob => "BLOB".to_string(),
-            DataType::Text => "LONGVARCHAR".to_string(),
-            DataType::Uuid => "UUID".to_string(),
-            DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
-            DataType::Enum(options) => format!("VARCHAR({})", options.max_length().unwrap()),
-            // TODO CHECK (mycol IN('a', 'b'))?
+        // There is no RESTRICT, restricting is the default
+        fn to_on_delete(&self, action: &ReferentialAction) -> Option<String> {
+            match action {
+                ReferentialAction::Restrict => None,
+                _ => Some(to_referential_action(action)),
+            }
+        }
+
+        fn to_on_update(&self, action: &ReferentialAction) -> Option<String> {
+            self.to_on_delete(action)
         }
-    }
-    pub fn to_column_ident(ident: &Ident) -> String {
-        ident.to_string()
     }
 }
 
 mod mysql {
     use crate::ast::{DataType, Ident};
 
-    pub fn to_additional_definitions(data_type: &DataType) -> Option<String> {
-        match data_type {
-            DataType::Enum(_) => None,
-            DataType::Integer
-            | DataType::SmallInteger
-            | DataType::AutoIncrement
-            | DataType::Float
-            | DataType::Boolean
-            | DataType::Date
-            | DataType::Time
-            | DataType::DateTime
-            | DataType::Varchar(_)
-            | DataType::Varbinary(_)
-            | DataType::Blob
-            | DataType::Text
-            | DataType::Uuid
-            | DataType::Decimal(_, _) => None,
+    use super::{to_values, EnumStrategy, SqlDialect};
+
+    pub struct MySQL;
+
+    impl SqlDialect for MySQL {
+        fn enum_strategy(&self) -> EnumStrategy {
+            EnumStrategy::Native
         }
-    }
 
-    pub fn to_data_type(data_type: &DataType) -> String {
-        match data_type {
-            DataType::Integer => "INTEGER".to_string(),
-            DataType::SmallInteger => "SMALLINT".to_string(),
-            DataType::AutoIncrement => "INTEGER AUTO_INCREMENT".to_string(),
-            DataType::Float => "FLOAT".to_string(),
-            DataType::Boolean => "BOOLEAN".to_string(),
-            DataType::Date => "DATE".to_string(),
-            DataType::Time => "TIME".to_string(),
-            DataType::DateTime => "DATETIME".to_string(),
-            DataType::Varchar(n) => format!("VARCHAR({})", n),
-            DataType::Varbinary(n) => format!("VARBINARY({n})"),
-            DataType::Blob => "BLOB".to_string(),
-            DataType::Text => "TEXT".to_string(),
-            DataType::Uuid => "UUID".to_string(),
-            DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
-            DataType::Enum(options) => format!(
-                "ENUM({})",
-                options
-                    .iter()
-                    .map(|x| format!("'{}'", x.replace("'", "\'")))
-                    .collect::<Vec<_>>()
-                    .join(",")
-            ),
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "SMALLINT".to_string(),
+                DataType::AutoIncrement => "INTEGER AUTO_INCREMENT".to_string(),
+                DataType::Float => "FLOAT".to_string(),
+                DataType::Boolean => "BOOLEAN".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "DATETIME".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR({})", n),
+                DataType::Varbinary(n) => format!("VARBINARY({n})"),
+                DataType::Blob => "BLOB".to_string(),
+                DataType::Text => "TEXT".to_string(),
+                DataType::Uuid => "UUID".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                DataType::Enum(options) => format!("ENUM({})", to_values(options)),
+            }
+        }
+
+        fn to_ident(&self, ident: &Ident) -> String {
+            self.quote(&ident.0)
+        }
+
+        fn quote(&self, name: &str) -> String {
+            format!("`{}`", name.replace('`', "``"))
+        }
+
+        // See https://dev.mysql.com/doc/refman/8.0/en/keywords.html
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "ACCESSIBLE",
+                "ANALYZE",
+                "CHANGE",
+                "CONDITION",
+                "DATABASE",
+                "DATABASES",
+                "DIV",
+                "DUAL",
+                "EXPLAIN",
+                "FULLTEXT",
+                "GROUPS",
+                "IF",
+                "IGNORE",
+                "INDEX",
+                "INTERVAL",
+                "KEY",
+                "KEYS",
+                "KILL",
+                "LIMIT",
+                "LINES",
+                "LOAD",
+                "LOCK",
+                "LONG",
+                "MOD",
+                "OPTION",
+                "RANGE",
+                "RANK",
+                "READ",
+                "REGEXP",
+                "RELEASE",
+                "RENAME",
+                "REPLACE",
+                "REQUIRE",
+                "RLIKE",
+                "ROW",
+                "ROWS",
+                "SCHEMA",
+                "SEPARATOR",
+                "SHOW",
+                "SIGNAL",
+                "SPATIAL",
+                "USAGE",
+                "USE",
+                "WRITE",
+                "XOR",
+                "ZEROFILL",
+            ]
         }
-    }
-    pub fn to_column_ident(ident: &Ident) -> String {
-        format!("`{}`", ident.to_string())
     }
 }
 
 mod postgresql {
-    use crate::ast::{DataType, Ident};
-    use std::{collections::HashMap, sync::Mutex};
+    use crate::ast::DataType;
 
-    lazy_static::lazy_static! {
-      static ref ENUM_IDX: Mutex<HashMap<Vec<String>, usize>> = Mutex::new(HashMap::new());
-    }
+    use super::{EnumStrategy, SqlDialect};
 
-    fn get_enum_name(options: &Vec<String>) -> String {
-        let mut map = ENUM_IDX.lock().unwrap();
+    pub struct PostgreSQL;
 
-        let idx = if let Some(x) = map.get(options) {
-            *x
-        } else {
-            let new = map.len();
-            map.insert(options.clone(), new);
-            new
-        };
+    impl SqlDialect for PostgreSQL {
+        fn enum_strategy(&self) -> EnumStrategy {
+            EnumStrategy::NamedType
+        }
 
-        format!("enum{}", idx)
-    }
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "SMALLINT".to_string(),
+                DataType::AutoIncrement => "SERIAL".to_string(),
+                DataType::Float => "FLOAT".to_string(),
+                DataType::Boolean => "BOOLEAN".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "DATETIME".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR({})", n),
+                DataType::Varbinary(n) => format!("VARBIT({n})"),
+                DataType::Blob => "BLOB".to_string(),
+                DataType::Text => "TEXT".to_string(),
+                DataType::Uuid => "UUID".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                // The columns use a type that is created for the enum
+                DataType::Enum(_) => "TEXT".to_string(),
+            }
+        }
 
-    pub fn to_additional_definitions(data_type: &DataType) -> Option<String> {
-        match data_type {
-            // TODO name and enum
-            DataType::Enum(options) => Some(format!(
-                "CREATE TYPE {} AS ENUM ({});",
-                get_enum_name(options),
-                options
-                    .iter()
-                    .map(|x| format!("'{}'", x.replace("'", "\'")))
-                    .collect::<Vec<_>>()
-                    .join(",")
-            )),
-            DataType::Integer
-            | DataType::SmallInteger
-            | DataType::AutoIncrement
-            | DataType::Float
-            | DataType::Boolean
-            | DataType::Date
-            | DataType::Time
-            | DataType::DateTime
-            | DataType::Varchar(_)
-            | DataType::Varbinary(_)
-            | DataType::Blob
-            | DataType::Text
-            | DataType::Uuid
-            | DataType::Decimal(_, _) => None,
+        // See https://www.postgresql.org/docs/current/sql-keywords-appendix.html
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "ANALYSE",
+                "ANALYZE",
+                "ARRAY",
+                "ASYMMETRIC",
+                "BINARY",
+                "BOTH",
+                "CAST",
+                "COLLATION",
+                "CONCURRENTLY",
+                "DEFERRABLE",
+                "DO",
+                "FETCH",
+                "FREEZE",
+                "ILIKE",
+                "INITIALLY",
+                "ISNULL",
+                "LATERAL",
+                "LEADING",
+                "LIMIT",
+                "LOCALTIME",
+                "LOCALTIMESTAMP",
+                "NOTNULL",
+                "OFFSET",
+                "ONLY",
+                "OVERLAPS",
+                "PLACING",
+                "RETURNING",
+                "SIMILAR",
+                "SYMMETRIC",
+                "TABLESAMPLE",
+                "TRAILING",
+                "VARIADIC",
+                "VERBOSE",
+                "WINDOW",
+            ]
         }
     }
+}
 
-    pub fn to_data_type(data_type: &DataType) -> String {
-        match data_type {
-            DataType::Integer => "INTEGER".to_string(),
-            DataType::SmallInteger => "SMALLINT".to_string(),
-            DataType::AutoIncrement => "SERIAL".to_string(),
-            DataType::Float => "FLOAT".to_string(),
-            DataType::Boolean => "BOOLEAN".to_string(),
-            DataType::Date => "DATE".to_string(),
-            DataType::Time => "TIME".to_string(),
-            DataType::DateTime => "DATETIME".to_string(),
-            DataType::Varchar(n) => format!("VARCHAR({})", n),
-            DataType::Varbinary(n) => format!("VARBIT({n})"),
-            DataType::Blob => "BLOB".to_string(),
-            DataType::Text => "TEXT".to_string(),
-            DataType::Uuid => "UUID".to_string(),
-            DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
-            DataType::Enum(options) => get_enum_name(options),
+mod mssql {
+    use crate::ast::{DataType, ReferentialAction};
+
+    use super::{to_referential_action, MaxLength, SqlDialect};
+
+    pub struct MSSQL;
+
+    impl SqlDialect for MSSQL {
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "INTEGER".to_string(), // TODO
+                DataType::AutoIncrement => "INTEGER IDENTITY(1,1)".to_string(),
+                DataType::Float => "FLOAT".to_string(),
+                DataType::Boolean => "BOOLEAN".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "DATETIME".to_string(),
+                DataType::Varchar(n) => format!("NVARCHAR({n})"),
+                DataType::Varbinary(n) => format!("VARBINARY({n})"),
+                DataType::Blob => "VARBINARY(max)".to_string(),
+                DataType::Text => "NVARCHAR(max)".to_string(),
+                DataType::Uuid => "UNIQUEIDENTIFIER".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                DataType::Enum(options) => format!("VARCHAR({})", options.max_length()),
+            }
+        }
+
+        fn quote(&self, name: &str) -> String {
+            format!("[{}]", name.replace(']', "]]"))
+        }
+
+        // Booleans are stored as numbers
+        fn to_default_value(&self, value: &str) -> String {
+            match value {
+                "true" => "1".to_string(),
+                "false" => "0".to_string(),
+                _ => value.to_string(),
+            }
+        }
+
+        // There is no RESTRICT, NO ACTION is checked at the same moment
+        fn to_on_delete(&self, action: &ReferentialAction) -> Option<String> {
+            match action {
+                ReferentialAction::Restrict => Some("NO ACTION".to_string()),
+                _ => Some(to_referential_action(action)),
+            }
+        }
+
+        fn to_on_update(&self, action: &ReferentialAction) -> Option<String> {
+            self.to_on_delete(action)
+        }
+
+        // See https://learn.microsoft.com/en-us/sql/t-sql/language-elements/reserved-keywords-transact-sql
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "BACKUP",
+                "BREAK",
+                "BROWSE",
+                "BULK",
+                "CHECKPOINT",
+                "CLUSTERED",
+                "COMPUTE",
+                "CONTAINS",
+                "CONTINUE",
+                "DATABASE",
+                "DBCC",
+                "DENY",
+                "DISK",
+                "DUMP",
+                "ERRLVL",
+                "EXEC",
+                "FILE",
+                "FILLFACTOR",
+                "FREETEXT",
+                "FUNCTION",
+                "GOTO",
+                "HOLDLOCK",
+                "IDENTITY",
+                "IF",
+                "INDEX",
+                "KEY",
+                "KILL",
+                "LINENO",
+                "LOAD",
+                "MERGE",
+                "NOCHECK",
+                "NONCLUSTERED",
+                "OFF",
+                "OFFSETS",
+                "OPEN",
+                "OVER",
+                "PERCENT",
+                "PIVOT",
+                "PLAN",
+                "PRINT",
+                "PROC",
+                "PROCEDURE",
+                "PUBLIC",
+                "RAISERROR",
+                "READ",
+                "RESTORE",
+                "RETURN",
+                "REVERT",
+                "ROWCOUNT",
+                "RULE",
+                "SAVE",
+                "SCHEMA",
+                "SHUTDOWN",
+                "STATISTICS",
+                "TEXTSIZE",
+                "TOP",
+                "TRAN",
+                "TRANSACTION",
+                "TRIGGER",
+                "TRUNCATE",
+                "TSEQUAL",
+                "UNPIVOT",
+                "USE",
+                "VIEW",
+                "WAITFOR",
+                "WHILE",
+            ]
         }
-    }
-    pub fn to_column_ident(ident: &Ident) -> String {
-        ident.to_string()
     }
 }
 
-mod mssql {
-    use crate::ast::{DataType, Ident};
+mod sqlite {
+    use crate::ast::DataType;
+
+    use super::SqlDialect;
 
-    use super::MaxLength;
-
-    pub fn to_additional_definitions(data_type: &DataType) -> Option<String> {
-        match data_type {
-            DataType::Enum(_) => None, // TODO?
-            DataType::Integer
-            | DataType::SmallInteger
-            | DataType::AutoIncrement
-            | DataType::Float
-            | DataType::Boolean
-            | DataType::Date
-            | DataType::Time
-            | DataType::DateTime
-            | DataType::Varchar(_)
-            | DataType::Varbinary(_)
-            | DataType::Blob
-            | DataType::Text
-            | DataType::Uuid
-            | DataType::Decimal(_, _) => None,
+    pub struct SQLite;
+
+    impl SqlDialect for SQLite {
+        // The names are chosen to get the right type affinity,
+        // see https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "SMALLINT".to_string(),
+                // Only an INTEGER PRIMARY KEY can be AUTOINCREMENT
+                DataType::AutoIncrement => "INTEGER PRIMARY KEY AUTOINCREMENT".to_string(),
+                DataType::Float => "REAL".to_string(),
+                DataType::Boolean => "BOOLEAN".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "DATETIME".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR({n})"),
+                DataType::Varbinary(_) => "BLOB".to_string(),
+                DataType::Blob => "BLOB".to_string(),
+                DataType::Text => "TEXT".to_string(),
+                DataType::Uuid => "TEXT".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                DataType::Enum(_) => "TEXT".to_string(),
+            }
+        }
+
+        fn declares_primary_key(&self, data_type: &DataType) -> bool {
+            matches!(data_type, DataType::AutoIncrement)
+        }
+
+        fn has_inline_foreign_keys(&self) -> bool {
+            true
+        }
+
+        // See https://www.sqlite.org/lang_keywords.html
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "ABORT",
+                "AUTOINCREMENT",
+                "CONFLICT",
+                "DATABASE",
+                "DEFERRABLE",
+                "EXPLAIN",
+                "GLOB",
+                "IF",
+                "INDEX",
+                "INDEXED",
+                "ISNULL",
+                "LIMIT",
+                "NOTNULL",
+                "OFFSET",
+                "PRAGMA",
+                "RAISE",
+                "REGEXP",
+                "REINDEX",
+                "RENAME",
+                "REPLACE",
+                "TRANSACTION",
+                "VACUUM",
+                "VIEW",
+            ]
         }
     }
+}
+
+mod oracle {
+    use crate::ast::{DataType, Ident, ReferentialAction};
+
+    use super::{to_referential_action, MaxLength, SqlDialect};
+
+    pub struct Oracle;
+
+    impl SqlDialect for Oracle {
+        // See https://docs.oracle.com/en/database/oracle/oracle-database/19/sqlrf/Data-Types.html
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "NUMBER(10)".to_string(),
+                DataType::SmallInteger => "NUMBER(5)".to_string(),
+                DataType::AutoIncrement => {
+                    "NUMBER(10) GENERATED BY DEFAULT AS IDENTITY".to_string()
+                }
+                DataType::Float => "BINARY_DOUBLE".to_string(),
+                DataType::Boolean => "NUMBER(1)".to_string(),
+                DataType::Date => "DATE".to_string(),
+                // There is no TIME, a time of day is stored as an interval since midnight
+                DataType::Time => "INTERVAL DAY(0) TO SECOND(0)".to_string(),
+                DataType::DateTime => "TIMESTAMP".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR2({n})"),
+                DataType::Varbinary(n) => format!("RAW({n})"),
+                DataType::Blob => "BLOB".to_string(),
+                DataType::Text => "CLOB".to_string(),
+                DataType::Uuid => "RAW(16)".to_string(),
+                DataType::Decimal(m, d) => format!("NUMBER({m}, {d})"),
+                DataType::Enum(options) => format!("VARCHAR2({})", options.max_length()),
+            }
+        }
+
+        fn to_column_constraint(&self, ident: &Ident, data_type: &DataType) -> Option<String> {
+            match data_type {
+                DataType::Boolean => Some(format!("CHECK ({} IN (0,1))", self.to_ident(ident))),
+                _ => None,
+            }
+        }
+
+        // Booleans are stored as numbers
+        fn to_default_value(&self, value: &str) -> String {
+            match value {
+                "true" => "1".to_string(),
+                "false" => "0".to_string(),
+                _ => value.to_string(),
+            }
+        }
+
+        // Only CASCADE and SET NULL exist, restricting is the default
+        fn to_on_delete(&self, action: &ReferentialAction) -> Option<String> {
+            match action {
+                ReferentialAction::Restrict => None,
+                _ => Some(to_referential_action(action)),
+            }
+        }
+
+        // There is no ON UPDATE
+        fn to_on_update(&self, _action: &ReferentialAction) -> Option<String> {
+            None
+        }
 
-    pub fn to_data_type(data_type: &DataType) -> String {
-        match data_type {
-            DataType::Integer => "INTEGER".to_string(),
-            DataType::SmallInteger => "INTEGER".to_string(), // TODO
-            DataType::AutoIncrement => "INTEGER IDENTITY(1,1)".to_string(),
-            DataType::Float => "FLOAT".to_string(),
-            DataType::Boolean => "BOOLEAN".to_string(),
-            DataType::Date => "DATE".to_string(),
-            DataType::Time => "TIME".to_string(),
-            DataType::DateTime => "DATETIME".to_string(),
-            DataType::Varchar(n) => format!("NVARCHAR({n})"),
-            DataType::Varbinary(n) => format!("VARBINARY({n})"),
-            DataType::Blob => "VARBINARY(max)".to_string(),
-            DataType::Text => "NVARCHAR(max)".to_string(),
-            DataType::Uuid => "UNIQUEIDENTIFIER".to_string(),
-            DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
-            DataType::Enum(options) => format!("VARCHAR({})", options.max_length().unwrap()),
-            // TODO CHECK (mycol IN('a', 'b'))
-            // See https://stackoverflow.com/a/1434338
+        // See https://docs.oracle.com/en/database/oracle/oracle-database/19/sqlrf/Oracle-SQL-Reserved-Words.html
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "ACCESS",
+                "AUDIT",
+                "CHAR",
+                "CLUSTER",
+                "COMMENT",
+                "COMPRESS",
+                "CONNECT",
+                "DATE",
+                "DECIMAL",
+                "EXCLUSIVE",
+                "FILE",
+                "FLOAT",
+                "IDENTIFIED",
+                "IMMEDIATE",
+                "INCREMENT",
+                "INDEX",
+                "INITIAL",
+                "INTEGER",
+                "LEVEL",
+                "LOCK",
+                "LONG",
+                "MAXEXTENTS",
+                "MINUS",
+                "MLSLABEL",
+                "MODE",
+                "MODIFY",
+                "NOAUDIT",
+                "NOCOMPRESS",
+                "NOWAIT",
+                "NUMBER",
+                "OFFLINE",
+                "ONLINE",
+                "OPTION",
+                "PCTFREE",
+                "PRIOR",
+                "PUBLIC",
+                "RAW",
+                "RENAME",
+                "RESOURCE",
+                "ROW",
+                "ROWID",
+                "ROWNUM",
+                "ROWS",
+                "SESSION",
+                "SHARE",
+                "SIZE",
+                "SMALLINT",
+                "START",
+                "SUCCESSFUL",
+                "SYNONYM",
+                "SYSDATE",
+                "TRIGGER",
+                "UID",
+                "VALIDATE",
+                "VARCHAR",
+                "VARCHAR2",
+                "VIEW",
+                "WHENEVER",
+            ]
         }
     }
-    pub fn to_column_ident(ident: &Ident) -> String {
-        ident.to_string()
+}
+
+mod db2 {
+    use crate::ast::{DataType, ReferentialAction};
+
+    use super::{to_referential_action, MaxLength, SqlDialect};
+
+    pub struct Db2;
+
+    impl SqlDialect for Db2 {
+        fn to_data_type(&self, data_type: &DataType) -> String {
+            match data_type {
+                DataType::Integer => "INTEGER".to_string(),
+                DataType::SmallInteger => "SMALLINT".to_string(),
+                DataType::AutoIncrement => "INTEGER GENERATED BY DEFAULT AS IDENTITY".to_string(),
+                DataType::Float => "DOUBLE".to_string(),
+                DataType::Boolean => "BOOLEAN".to_string(),
+                DataType::Date => "DATE".to_string(),
+                DataType::Time => "TIME".to_string(),
+                DataType::DateTime => "TIMESTAMP".to_string(),
+                DataType::Varchar(n) => format!("VARCHAR({n})"),
+                DataType::Varbinary(n) => format!("VARBINARY({n})"),
+                DataType::Blob => "BLOB".to_string(),
+                DataType::Text => "CLOB".to_string(),
+                DataType::Uuid => "CHAR(16) FOR BIT DATA".to_string(),
+                DataType::Decimal(m, d) => format!("DECIMAL({m}, {d})"),
+                DataType::Enum(options) => format!("VARCHAR({})", options.max_length()),
+            }
+        }
+
+        fn requires_not_null_key(&self) -> bool {
+            true
+        }
+
+        // Updated keys can't be followed, only RESTRICT and NO ACTION exist
+        fn to_on_update(&self, action: &ReferentialAction) -> Option<String> {
+            match action {
+                ReferentialAction::Restrict => Some(to_referential_action(action)),
+                _ => None,
+            }
+        }
+
+        fn reserved_words(&self) -> &[&str] {
+            &[
+                "ALIAS",
+                "CALL",
+                "CONCAT",
+                "CURRENT",
+                "CURSOR",
+                "DATABASE",
+                "DAYS",
+                "FETCH",
+                "FINAL",
+                "HOURS",
+                "INDEX",
+                "KEY",
+                "LABEL",
+                "LOCK",
+                "MINUTES",
+                "MONTHS",
+                "PROCEDURE",
+                "RESULT",
+                "ROWS",
+                "SCHEMA",
+                "SECONDS",
+                "SEQUENCE",
+                "TRIGGER",
+                "VIEW",
+                "YEARS",
+            ]
+        }
     }
 }
 
+/// The words that are reserved by the sql standard and by most dbms'es
+const RESERVED: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "COLUMN",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "CURRENT_DATE",
+    "CURRENT_TIME",
+    "CURRENT_TIMESTAMP",
+    "CURRENT_USER",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "EXISTS",
+    "FALSE",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GRANT",
+    "GROUP",
+    "HAVING",
+    "IN",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "LEFT",
+    "LIKE",
+    "NATURAL",
+    "NOT",
+    "NULL",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "REFERENCES",
+    "REVOKE",
+    "RIGHT",
+    "SELECT",
+    "SESSION_USER",
+    "SET",
+    "SOME",
+    "TABLE",
+    "THEN",
+    "TO",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USER",
+    "USING",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+/// The values of an enum as a list of sql strings. A quote in a value is doubled, which
+/// every dbms accepts (MySQL used to get it unescaped).
+fn to_values(options: &[String]) -> String {
+    options
+        .iter()
+        .map(|x| format!("'{}'", x.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 trait MaxLength {
-    fn max_length(&self) -> Option<usize>;
+    /// The length of the longest value, at least 1 so it is a valid length. An enum
+    /// without values is reported by the validation of the physical description.
+    fn max_length(&self) -> usize;
 }
 
 impl MaxLength for Vec<String> {
-    fn max_length(&self) -> Option<usize> {
-        self.iter().map(|x| x.len()).max()
+    fn max_length(&self) -> usize {
+        self.iter().map(|x| x.len()).max().unwrap_or(0).max(1)
     }
 }
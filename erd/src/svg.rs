@@ -0,0 +1,511 @@
+// Draws the graphs made by `ToDot` without Graphviz.
+// Only the parts of the dot language that are used by erd-script are supported.
+use crate::dot::{AttributeList, Graph, Statement};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const FONT_SIZE: f64 = 14.0;
+/// Estimated width of a character, there is no font information to measure text
+const CHAR_WIDTH: f64 = 7.5;
+const POINTS_PER_INCH: f64 = 72.0;
+const MARGIN: f64 = 20.0;
+/// Distance between the edges that connect the same two nodes
+const PARALLEL_EDGE_DISTANCE: f64 = 40.0;
+
+const ITERATIONS: usize = 500;
+const REPULSION: f64 = 20000.0;
+const SPRING: f64 = 0.3;
+const GRAVITY: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shape {
+    Box,
+    Ellipse,
+    Diamond,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Label {
+    text: String,
+    underline: bool,
+    color: Option<String>,
+}
+
+impl Label {
+    /// Supports quoted labels and the html labels with `<U>` and `<font color="...">`
+    fn parse(value: &str) -> Self {
+        if let Some(html) = value.strip_prefix('<').and_then(|v| v.strip_suffix('>')) {
+            let color = html
+                .find("color=\"")
+                .and_then(|i| html[i + 7..].split('"').next())
+                .map(|c| c.to_string());
+            let mut text = String::new();
+            let mut in_tag = false;
+            for c in html.chars() {
+                match c {
+                    '<' => in_tag = true,
+                    '>' => in_tag = false,
+                    c if !in_tag => text.push(c),
+                    _ => (),
+                }
+            }
+            Self {
+                text,
+                underline: html.contains("<U>"),
+                color,
+            }
+        } else {
+            Self {
+                text: value.trim_matches('"').to_string(),
+                underline: false,
+                color: None,
+            }
+        }
+    }
+
+    fn width(&self) -> f64 {
+        self.text.chars().count() as f64 * CHAR_WIDTH
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    shape: Shape,
+    label: Label,
+    /// Weak entities and identifying relations have a double border
+    double: bool,
+    /// Partial keys have a dashed border
+    dashed: bool,
+    half_width: f64,
+    half_height: f64,
+    x: f64,
+    y: f64,
+}
+
+impl Node {
+    fn new(shape: Shape, label: Label) -> Self {
+        let (half_width, half_height) = match shape {
+            Shape::Box => (label.width() / 2.0 + 12.0, 18.0),
+            Shape::Ellipse => (label.width() / 2.0 * 1.3 + 12.0, 18.0),
+            Shape::Diamond => (label.width() * 0.8 + 16.0, 28.0),
+        };
+        Self {
+            shape,
+            label,
+            double: false,
+            dashed: false,
+            half_width,
+            half_height,
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+
+    fn radius(&self) -> f64 {
+        (self.half_width + self.half_height) / 2.0
+    }
+
+    /// The point on the border of the node in the direction of (x, y)
+    fn border_towards(&self, x: f64, y: f64) -> (f64, f64) {
+        let (dx, dy) = (x - self.x, y - self.y);
+        if dx == 0.0 && dy == 0.0 {
+            return (self.x, self.y);
+        }
+        let scale = match self.shape {
+            Shape::Box => 1.0 / (dx.abs() / self.half_width).max(dy.abs() / self.half_height),
+            Shape::Ellipse => {
+                1.0 / ((dx / self.half_width).powi(2) + (dy / self.half_height).powi(2)).sqrt()
+            }
+            Shape::Diamond => 1.0 / (dx.abs() / self.half_width + dy.abs() / self.half_height),
+        };
+        (self.x + dx * scale, self.y + dy * scale)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<Label>,
+    double: bool,
+    len: f64,
+}
+
+fn attribute<'a>(attributes: &'a Option<AttributeList>, key: &str) -> Option<&'a str> {
+    let mut list = attributes.as_ref();
+    while let Some(l) = list {
+        if let Some(item) = l.content.0.iter().find(|i| i.key == key) {
+            return Some(&item.value);
+        }
+        list = l.tail.as_ref().as_ref();
+    }
+    None
+}
+
+fn collect(graph: &Graph) -> (Vec<Node>, Vec<Edge>) {
+    let mut nodes = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut node_index = |nodes: &mut Vec<Node>, id: &str| {
+        *indices.entry(id.to_string()).or_insert_with(|| {
+            nodes.push(Node::new(Shape::Ellipse, Label::parse(id)));
+            nodes.len() - 1
+        })
+    };
+
+    let mut edges = Vec::new();
+    for statement in graph.statements.iter() {
+        match statement {
+            Statement::Node(n) => {
+                let shape = match attribute(&n.attributes, "shape") {
+                    Some("box") => Shape::Box,
+                    Some("diamond") => Shape::Diamond,
+                    _ => Shape::Ellipse,
+                };
+                let label = Label::parse(attribute(&n.attributes, "label").unwrap_or(&n.node));
+                let index = node_index(&mut nodes, &n.node);
+                nodes[index] = Node {
+                    double: attribute(&n.attributes, "peripheries") == Some("2"),
+                    dashed: attribute(&n.attributes, "style") == Some("dashed"),
+                    ..Node::new(shape, label)
+                };
+            }
+            Statement::Edge(e) => {
+                let mut from = node_index(&mut nodes, &e.left);
+                let mut rhs = Some(&e.right);
+                while let Some(r) = rhs {
+                    let to = node_index(&mut nodes, &r.id);
+                    edges.push(Edge {
+                        from,
+                        to,
+                        label: attribute(&e.attributes, "label").map(Label::parse),
+                        double: attribute(&e.attributes, "color")
+                            .map(|c| c.contains(':'))
+                            .unwrap_or(false),
+                        len: attribute(&e.attributes, "len")
+                            .and_then(|l| l.parse().ok())
+                            .unwrap_or(1.0),
+                    });
+                    from = to;
+                    rhs = r.right.as_ref().as_ref();
+                }
+            }
+            _ => (),
+        }
+    }
+    (nodes, edges)
+}
+
+/// Nodes with more than one neighbour start on a spiral, the others (e.g. attributes)
+/// start in a circle around their neighbour.
+fn start_positions(nodes: &mut [Node], edges: &[Edge]) {
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for edge in edges.iter().filter(|e| e.from != e.to) {
+        neighbours[edge.from].push(edge.to);
+        neighbours[edge.to].push(edge.from);
+    }
+    let is_leaf = |i: usize| neighbours[i].len() == 1 && neighbours[neighbours[i][0]].len() > 1;
+
+    let centers = (0..nodes.len()).filter(|i| !is_leaf(*i));
+    for (placed, i) in centers.enumerate() {
+        let angle = placed as f64 * 2.399_963; // golden angle
+        let radius = 150.0 * ((placed + 1) as f64).sqrt();
+        nodes[i].x = radius * angle.cos();
+        nodes[i].y = radius * angle.sin();
+    }
+    for (center, leaves) in neighbours.iter().enumerate() {
+        let leaves: Vec<_> = leaves.iter().filter(|l| is_leaf(**l)).collect();
+        for (k, leaf) in leaves.iter().enumerate() {
+            let angle = k as f64 * std::f64::consts::TAU / leaves.len() as f64;
+            nodes[**leaf].x = nodes[center].x + 100.0 * angle.cos();
+            nodes[**leaf].y = nodes[center].y + 100.0 * angle.sin();
+        }
+    }
+}
+
+/// A force directed layout: all nodes repel each other and edges pull their nodes
+/// to the length of the edge. The start positions are fixed, so the result is deterministic.
+fn layout(nodes: &mut [Node], edges: &[Edge]) {
+    start_positions(nodes, edges);
+
+    let mut temperature = 60.0;
+    for _ in 0..ITERATIONS {
+        let mut forces = vec![(0.0, 0.0); nodes.len()];
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                let (dx, dy) = (nodes[i].x - nodes[j].x, nodes[i].y - nodes[j].y);
+                let distance = (dx * dx + dy * dy).sqrt().max(1.0);
+                let gap = (distance - nodes[i].radius() - nodes[j].radius()).max(10.0);
+                let force = REPULSION / (gap * gap);
+                forces[i].0 += dx / distance * force;
+                forces[i].1 += dy / distance * force;
+                forces[j].0 -= dx / distance * force;
+                forces[j].1 -= dy / distance * force;
+            }
+        }
+        for edge in edges.iter().filter(|e| e.from != e.to) {
+            let (from, to) = (&nodes[edge.from], &nodes[edge.to]);
+            let (dx, dy) = (to.x - from.x, to.y - from.y);
+            let distance = (dx * dx + dy * dy).sqrt().max(1.0);
+            let ideal = edge.len * POINTS_PER_INCH + from.radius() + to.radius();
+            let force = (distance - ideal) * SPRING;
+            forces[edge.from].0 += dx / distance * force;
+            forces[edge.from].1 += dy / distance * force;
+            forces[edge.to].0 -= dx / distance * force;
+            forces[edge.to].1 -= dy / distance * force;
+        }
+        for (node, (fx, fy)) in nodes.iter_mut().zip(forces) {
+            let (fx, fy) = (fx - node.x * GRAVITY, fy - node.y * GRAVITY);
+            let magnitude = (fx * fx + fy * fy).sqrt();
+            if magnitude > 0.0 {
+                let step = magnitude.min(temperature);
+                node.x += fx / magnitude * step;
+                node.y += fy / magnitude * step;
+            }
+        }
+        temperature = (temperature * 0.99).max(1.0);
+    }
+    remove_overlap(nodes);
+}
+
+/// Pushes overlapping nodes apart along the line between their centers
+fn remove_overlap(nodes: &mut [Node]) {
+    for _ in 0..100 {
+        let mut moved = false;
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                let (dx, dy) = (nodes[j].x - nodes[i].x, nodes[j].y - nodes[i].y);
+                let overlap_x = nodes[i].half_width + nodes[j].half_width + 8.0 - dx.abs();
+                let overlap_y = nodes[i].half_height + nodes[j].half_height + 8.0 - dy.abs();
+                if overlap_x > 0.0 && overlap_y > 0.0 {
+                    moved = true;
+                    let direction = |d: f64| if d >= 0.0 { 1.0 } else { -1.0 };
+                    // Move along the axis that needs the smallest move
+                    let (mx, my) = if overlap_x < overlap_y {
+                        (overlap_x / 2.0 * direction(dx), 0.0)
+                    } else {
+                        (0.0, overlap_y / 2.0 * direction(dy))
+                    };
+                    nodes[i].x -= mx;
+                    nodes[i].y -= my;
+                    nodes[j].x += mx;
+                    nodes[j].y += my;
+                }
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_label(s: &mut String, label: &Label, x: f64, y: f64) -> std::fmt::Result {
+    write!(
+        s,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" dominant-baseline="central" font-family="Times,serif" font-size="{}""#,
+        x, y, FONT_SIZE
+    )?;
+    if let Some(color) = &label.color {
+        write!(s, r#" fill="{}""#, escape(color))?;
+    }
+    if label.underline {
+        write!(s, r#" text-decoration="underline""#)?;
+    }
+    writeln!(s, ">{}</text>", escape(&label.text))
+}
+
+fn write_node(s: &mut String, node: &Node) -> std::fmt::Result {
+    let style = if node.dashed {
+        r#"fill="white" stroke="black" stroke-dasharray="5,3""#
+    } else {
+        r#"fill="white" stroke="black""#
+    };
+    // The inner border of a double border is drawn inside the outer one
+    let insets: &[f64] = if node.double { &[0.0, 4.0] } else { &[0.0] };
+    for inset in insets.iter() {
+        let (half_width, half_height) = (node.half_width - inset, node.half_height - inset);
+        match node.shape {
+            Shape::Box => writeln!(
+                s,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" {}/>"#,
+                node.x - half_width,
+                node.y - half_height,
+                half_width * 2.0,
+                half_height * 2.0,
+                style
+            )?,
+            Shape::Ellipse => writeln!(
+                s,
+                r#"<ellipse cx="{:.1}" cy="{:.1}" rx="{:.1}" ry="{:.1}" {}/>"#,
+                node.x, node.y, half_width, half_height, style
+            )?,
+            Shape::Diamond => {
+                // The diamond is wider than high, its inner border needs a larger inset
+                let half_width = node.half_width - inset * node.half_width / node.half_height;
+                writeln!(
+                    s,
+                    r#"<polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" {}/>"#,
+                    node.x,
+                    node.y - half_height,
+                    node.x + half_width,
+                    node.y,
+                    node.x,
+                    node.y + half_height,
+                    node.x - half_width,
+                    node.y,
+                    style
+                )?
+            }
+        }
+    }
+    write_label(s, &node.label, node.x, node.y)
+}
+
+/// Edges between the same nodes are drawn as curves next to each other
+fn write_edge(s: &mut String, nodes: &[Node], edge: &Edge, offset: f64) -> std::fmt::Result {
+    let (from, to) = (&nodes[edge.from], &nodes[edge.to]);
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt().max(1.0);
+    let (nx, ny) = (-dy / length, dx / length);
+    let control = (
+        (from.x + to.x) / 2.0 + nx * offset * 2.0,
+        (from.y + to.y) / 2.0 + ny * offset * 2.0,
+    );
+    let start = from.border_towards(control.0, control.1);
+    let end = to.border_towards(control.0, control.1);
+
+    let lines: &[f64] = if edge.double { &[-2.0, 2.0] } else { &[0.0] };
+    for shift in lines.iter() {
+        writeln!(
+            s,
+            r#"<path d="M{:.1},{:.1} Q{:.1},{:.1} {:.1},{:.1}" fill="none" stroke="black"/>"#,
+            start.0 + nx * shift,
+            start.1 + ny * shift,
+            control.0 + nx * shift,
+            control.1 + ny * shift,
+            end.0 + nx * shift,
+            end.1 + ny * shift
+        )?;
+    }
+    if let Some(label) = &edge.label {
+        // The middle of the curve, moved a bit aside so the label doesn't cover the line
+        let x = 0.25 * start.0 + 0.5 * control.0 + 0.25 * end.0 + nx * 10.0;
+        let y = 0.25 * start.1 + 0.5 * control.1 + 0.25 * end.1 + ny * 10.0;
+        write_label(s, label, x, y)?;
+    }
+    Ok(())
+}
+
+/// Lays out and draws the graph as svg
+pub fn render(graph: &Graph) -> String {
+    let (mut nodes, edges) = collect(graph);
+    layout(&mut nodes, &edges);
+
+    let min_x = nodes.iter().map(|n| n.x - n.half_width).fold(0.0, f64::min) - MARGIN;
+    let min_y = nodes
+        .iter()
+        .map(|n| n.y - n.half_height)
+        .fold(0.0, f64::min)
+        - MARGIN;
+    let max_x = nodes.iter().map(|n| n.x + n.half_width).fold(0.0, f64::max) + MARGIN;
+    let max_y = nodes
+        .iter()
+        .map(|n| n.y + n.half_height)
+        .fold(0.0, f64::max)
+        + MARGIN;
+    for node in nodes.iter_mut() {
+        node.x -= min_x;
+        node.y -= min_y;
+    }
+
+    let mut s = String::new();
+    let _ = write_svg(&mut s, &nodes, &edges, max_x - min_x, max_y - min_y);
+    s
+}
+
+fn write_svg(
+    s: &mut String,
+    nodes: &[Node],
+    edges: &[Edge],
+    width: f64,
+    height: f64,
+) -> std::fmt::Result {
+    writeln!(
+        s,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#
+    )?;
+    writeln!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}pt" height="{:.0}pt" viewBox="0 0 {:.1} {:.1}">"#,
+        width, height, width, height
+    )?;
+    writeln!(
+        s,
+        r#"<rect width="100%" height="100%" fill="white" stroke="none"/>"#
+    )?;
+
+    let mut parallel: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    for edge in edges.iter() {
+        parallel
+            .entry((edge.from.min(edge.to), edge.from.max(edge.to)))
+            .or_insert((0, 0))
+            .0 += 1;
+    }
+    for edge in edges.iter() {
+        let key = (edge.from.min(edge.to), edge.from.max(edge.to));
+        let (count, index) = parallel.get_mut(&key).expect("all edges are counted");
+        let mut offset = (*index as f64 - (*count - 1) as f64 / 2.0) * PARALLEL_EDGE_DISTANCE;
+        if edge.from > edge.to {
+            // The normal of the edge is flipped
+            offset = -offset;
+        }
+        *index += 1;
+        write_edge(s, nodes, edge, offset)?;
+    }
+    for node in nodes.iter() {
+        write_node(s, node)?;
+    }
+    writeln!(s, "</svg>")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::erd::ERD;
+
+    #[test]
+    fn draw_erd() {
+        let erd = ERD::from_script(
+            "entity Person\n  id id\n  attribute name\n\nrelation Parent(Is parent of)\n  one optional Person as parent\n  multiple optional Person as child",
+        )
+        .unwrap();
+        let svg = render(&erd.to_dot());
+        assert_eq!(svg.matches("<rect").count(), 2); // background and Person
+        assert_eq!(svg.matches("<ellipse").count(), 2);
+        assert_eq!(svg.matches("<polygon").count(), 1);
+        assert_eq!(svg.matches("<path").count(), 4);
+        assert!(svg.contains(r#"text-decoration="underline">id</text>"#));
+        assert!(svg.contains(r#"fill="blue">1 parent</text>"#));
+        assert!(svg.contains(r#"fill="blue">n child</text>"#));
+        assert!(svg.contains(">Is parent of</text>"));
+        assert_eq!(svg, render(&erd.to_dot()));
+    }
+
+    #[test]
+    fn draw_weak_entity() {
+        let erd = ERD::from_script(
+            "entity Building\n  id id\n\nweak entity Room\n  id number\n\nidentifying relation Contains\n  one required Building\n  multiple optional Room",
+        )
+        .unwrap();
+        let svg = render(&erd.to_dot());
+        assert_eq!(svg.matches("<rect").count(), 4); // background, Building and twice Room
+        assert_eq!(svg.matches("<polygon").count(), 2);
+        assert_eq!(svg.matches("stroke-dasharray").count(), 1);
+    }
+}